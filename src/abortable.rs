@@ -0,0 +1,123 @@
+use std::fmt;
+#[cfg(feature = "async-task")]
+use std::sync::{Mutex, PoisonError};
+
+/// A handle to a task that can be aborted, whatever the executor running it.
+///
+/// `DropHandle<A>` aborts the task through this trait when its last clone is dropped.
+/// It is implemented for `tokio::task::AbortHandle` (with the `tokio` cargo feature),
+/// `futures::future::AbortHandle` (with the `futures` cargo feature),
+/// and `AsyncTaskHandle<T>`, wrapping the `Task<T>` of `async-task`, `async-executor` and `smol` (with the `async-task` cargo feature).
+///
+/// Example usage:
+/// ```
+/// use drop_handle::{Abortable, DropHandle};
+/// use std::sync::{
+///     Arc,
+///     atomic::{AtomicBool, Ordering},
+/// };
+///
+/// #[derive(Debug)]
+/// struct AbortFlag(Arc<AtomicBool>);
+///
+/// impl Abortable for AbortFlag {
+///     fn abort(&self) {
+///         self.0.store(true, Ordering::SeqCst);
+///     }
+/// }
+///
+/// let aborted = Arc::new(AtomicBool::new(false));
+/// let drop_handle = DropHandle::from_abortable(AbortFlag(aborted.clone()));
+/// drop(drop_handle.clone());
+/// assert!(!aborted.load(Ordering::SeqCst));
+/// drop(drop_handle);
+/// assert!(aborted.load(Ordering::SeqCst));
+/// ```
+pub trait Abortable: fmt::Debug + Send + Sync + 'static {
+    /// Aborts the task.
+    fn abort(&self);
+
+    /// Returns `true` if the task has finished, and `false` if it is still running or if the handle cannot tell.
+    ///
+    /// A `DropHandle` does not report a task that has already finished as aborted by its last drop.
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    /// Returns the id of the Tokio task, or `None` if this is not a Tokio task handle.
+    ///
+    /// A `DropHandle` stores it when it is created, to be compared, hashed and borrowed by task id.
+    #[cfg(feature = "tokio")]
+    fn tokio_id(&self) -> Option<tokio::task::Id> {
+        None
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Abortable for tokio::task::AbortHandle {
+    fn abort(&self) {
+        self.abort();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.is_finished()
+    }
+
+    fn tokio_id(&self) -> Option<tokio::task::Id> {
+        Some(self.id())
+    }
+}
+
+#[cfg(feature = "futures")]
+impl Abortable for futures_util::future::AbortHandle {
+    fn abort(&self) {
+        self.abort();
+    }
+}
+
+/// An abortable handle to the `Task<T>` of `async-task`, also used by `async-executor` and `smol`.
+///
+/// Such a task is cancelled when its `Task<T>` is dropped, so aborting it drops the wrapped `Task<T>`.
+#[cfg(feature = "async-task")]
+pub struct AsyncTaskHandle<T>(Mutex<Option<async_task::Task<T>>>);
+
+#[cfg(feature = "async-task")]
+impl<T> AsyncTaskHandle<T> {
+    /// Returns `true` if the task has finished, or has been aborted.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .is_none_or(async_task::Task::is_finished)
+    }
+}
+
+#[cfg(feature = "async-task")]
+impl<T> From<async_task::Task<T>> for AsyncTaskHandle<T> {
+    fn from(value: async_task::Task<T>) -> Self {
+        Self(Mutex::new(Some(value)))
+    }
+}
+
+#[cfg(feature = "async-task")]
+impl<T> fmt::Debug for AsyncTaskHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncTaskHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+#[cfg(feature = "async-task")]
+impl<T: Send + 'static> Abortable for AsyncTaskHandle<T> {
+    fn abort(&self) {
+        let task = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        drop(task);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.is_finished()
+    }
+}
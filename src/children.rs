@@ -0,0 +1,174 @@
+use crate::{AbortReason, Abortable, DropHandle};
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::{
+    mem,
+    sync::{Arc, Mutex, PoisonError, Weak},
+};
+
+/// Serialises the attachments of child tasks, so that two concurrent attachments cannot create a cycle.
+static ATTACH_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(feature = "tokio")]
+impl DropHandle {
+    /// Spawns a child task on the current Tokio runtime, attached to this task.
+    ///
+    /// See `attach_child()` for the semantics of child tasks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    ///
+    /// Example usage:
+    /// ```
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let parent = drop_handle::spawn(pending::<()>());
+    ///     let child = parent.spawn_child(pending::<()>());
+    ///     drop(parent);
+    ///     // The child task has been aborted along with its parent, even though `child` is still alive.
+    /// }
+    /// ```
+    #[track_caller]
+    #[allow(clippy::return_self_not_must_use)] // The parent task holds the child task
+    pub fn spawn_child<F>(&self, future: F) -> Self
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let child = crate::spawn(future);
+        self.attach_child(&child);
+        child
+    }
+}
+
+impl<A: Abortable> DropHandle<A> {
+    /// Attaches a child task to this task.
+    ///
+    /// The parent task holds its child tasks, which are kept alive as long as the parent task is.
+    /// When the parent task is aborted, either by its last `DropHandle` being dropped or with `DropHandle::abort()`,
+    /// its child tasks are recursively aborted too, even if other `DropHandle` still hold them.
+    /// A child task attached to a task that was already aborted or has finished is aborted right away.
+    /// When the last `DropHandle` of a detached task is dropped, its child tasks are detached too, as it keeps running.
+    ///
+    /// If the child task already has a parent, it is detached from it first.
+    /// The child tasks that have finished are released, when their task handle can tell, see `Abortable::is_finished()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the child task is this task or one of its ancestors, as this would create a cycle.
+    pub fn attach_child(&self, child: &Self) {
+        self.release_finished_children();
+        // Concurrent attachments could otherwise both pass the cycle check, e.g. attaching A to B and B to A
+        let attach_lock = ATTACH_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut ancestor = Some(self.0.clone());
+        while let Some(shared) = ancestor {
+            assert!(
+                !Arc::ptr_eq(&shared, &child.0),
+                "cannot attach task {:?} as a child of its descendant {:?}",
+                child.0.abort_handle,
+                self.0.abort_handle
+            );
+            ancestor = shared
+                .parent
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .upgrade();
+        }
+
+        child.detach_from_parent();
+        // Checked under the lock of the children, which `abort_children()` takes after recording the abort reason
+        let mut children = self
+            .0
+            .children
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if self.0.abort_reason().is_some() || self.0.is_finished() {
+            drop(children);
+            drop(attach_lock);
+            debug!(
+                task.id = %child.0.task_id(),
+                name = child.0.name(),
+                parent.id = %self.0.task_id(),
+                parent.name = self.0.name(),
+                reason = %AbortReason::ParentAborted,
+                "parent task is terminating: abort child task"
+            );
+            child.0.abort(AbortReason::ParentAborted);
+            return;
+        }
+        debug!(
+            task.id = %child.0.task_id(),
+            name = child.0.name(),
+            parent.id = %self.0.task_id(),
+            parent.name = self.0.name(),
+            "attach child task"
+        );
+        *child
+            .0
+            .parent
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Arc::downgrade(&self.0);
+        children.push(child.clone());
+    }
+
+    /// Detaches this task from its parent, so it is no longer aborted along with it.
+    ///
+    /// Returns `false` if this task has no parent.
+    pub fn detach_from_parent(&self) -> bool {
+        let parent = mem::take(&mut *self.0.parent.lock().unwrap_or_else(PoisonError::into_inner));
+        let Some(parent) = parent.upgrade() else {
+            return false;
+        };
+        debug!(
+            task.id = %self.0.task_id(),
+            name = self.0.name(),
+            parent.id = %parent.task_id(),
+            parent.name = parent.name(),
+            "detach child task"
+        );
+        parent
+            .children
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|child| !Arc::ptr_eq(&child.0, &self.0));
+        true
+    }
+
+    /// Returns the number of child tasks attached to this task, releasing the ones that have finished.
+    #[must_use]
+    pub fn children_count(&self) -> usize {
+        self.release_finished_children();
+        self.0
+            .children
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Releases the child tasks that have finished, so a long-lived parent does not keep them alive.
+    fn release_finished_children(&self) {
+        let finished: Vec<_> = {
+            let mut children = self
+                .0
+                .children
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let (finished, running) = mem::take(&mut *children)
+                .into_iter()
+                .partition(|child| child.0.is_finished());
+            *children = running;
+            finished
+        };
+        // The finished children are dropped outside of the lock, as it may be their last `DropHandle`
+        for child in finished {
+            *child
+                .0
+                .parent
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Weak::new();
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{AbortReason, DropHandle, shared::Shared};
+use std::{
+    sync::{Arc, Weak},
+    time::Duration,
+};
+use tokio::{
+    sync::watch,
+    task::AbortHandle,
+    time::{Instant, timeout_at},
+};
+
+impl DropHandle {
+    /// Sets a deadline after which the task is aborted, even if it is still held, and returns this `DropHandle`.
+    ///
+    /// See `set_deadline()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    ///
+    /// Example usage:
+    /// ```
+    /// use std::future::pending;
+    /// use tokio::time::{Duration, Instant};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>())
+    ///         .with_deadline(Instant::now() + Duration::from_secs(30));
+    ///     // The task will be aborted when `drop_handle` goes out of scope, or after 30 seconds, whichever comes first.
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        self.set_deadline(deadline);
+        self
+    }
+
+    /// Sets a deadline after which the task is aborted, even if it is still held.
+    ///
+    /// The deadline is shared by every clone of this `DropHandle`, so any of them can extend it, or clear it with `clear_deadline()`.
+    /// When it elapses, the abort is recorded as `AbortReason::Deadline`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime the first time a deadline is set.
+    pub fn set_deadline(&self, deadline: Instant) {
+        debug!(task.id = %self.0.task_id(), name = self.0.name(), ?deadline, "set deadline");
+        self.deadline_sender().send_replace(Some(Deadline {
+            at: deadline,
+            lease: None,
+        }));
+    }
+
+    /// Puts the task under a lease, and returns this `DropHandle`.
+    ///
+    /// See `set_lease()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    ///
+    /// Example usage:
+    /// ```
+    /// use std::future::pending;
+    /// use tokio::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>()).with_lease(Duration::from_secs(30));
+    ///     // The task will be aborted when `drop_handle` goes out of scope, or if it is not renewed within 30 seconds.
+    ///     drop_handle.renew();
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_lease(self, ttl: Duration) -> Self {
+        self.set_lease(ttl);
+        self
+    }
+
+    /// Puts the task under a lease, that must be renewed with `renew()` within `ttl`, or the task is aborted.
+    ///
+    /// The lease is a deadline set `ttl` from now, and pushed back by `ttl` at each renewal:
+    /// it is shared by every clone of this `DropHandle`, and replaces any deadline set with `set_deadline()`.
+    /// The task is still aborted when the last `DropHandle` is dropped.
+    /// When the lease expires, the abort is recorded as `AbortReason::LeaseExpired`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime the first time a deadline or a lease is set.
+    pub fn set_lease(&self, ttl: Duration) {
+        debug!(task.id = %self.0.task_id(), name = self.0.name(), ?ttl, "set lease");
+        self.deadline_sender().send_replace(Some(Deadline {
+            at: Instant::now() + ttl,
+            lease: Some(ttl),
+        }));
+    }
+
+    /// Renews the lease of the task, so it expires `ttl` from now.
+    ///
+    /// Returns `false` if the task is not under a lease, e.g. because it has been cleared with `clear_deadline()`,
+    /// or if it is too late: the lease has already expired, or the task was aborted or has finished.
+    #[allow(clippy::must_use_candidate)] // Renewing is the point, the result is only informative
+    pub fn renew(&self) -> bool {
+        let Some(sender) = self.0.deadline.get() else {
+            return false;
+        };
+        if self.0.abort_reason().is_some() || self.0.is_finished() {
+            return false;
+        }
+        let now = Instant::now();
+        sender.send_if_modified(|deadline| match deadline {
+            Some(Deadline {
+                at,
+                lease: Some(ttl),
+            }) if *at > now => {
+                *at = now + *ttl;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Clears the deadline or the lease, so the task is only aborted when the last `DropHandle` is dropped.
+    pub fn clear_deadline(&self) {
+        if let Some(deadline) = self.0.deadline.get() {
+            debug!(task.id = %self.0.task_id(), name = self.0.name(), "clear deadline");
+            deadline.send_replace(None);
+        }
+    }
+
+    /// Returns the deadline after which the task is aborted, if any, including the expiry of its lease.
+    #[must_use]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.0
+            .deadline
+            .get()
+            .and_then(|deadline| deadline.borrow().map(|deadline| deadline.at))
+    }
+
+    /// Returns the sender updating the deadline, starting its watcher the first time.
+    fn deadline_sender(&self) -> &watch::Sender<Option<Deadline>> {
+        self.0
+            .deadline
+            .get_or_init(|| watch_deadline(Arc::downgrade(&self.0)))
+    }
+}
+
+/// When the task is aborted, and with which lease, if any.
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    at: Instant,
+    lease: Option<Duration>,
+}
+
+impl Deadline {
+    const fn reason(self) -> AbortReason {
+        if self.lease.is_some() {
+            AbortReason::LeaseExpired
+        } else {
+            AbortReason::Deadline
+        }
+    }
+}
+
+/// Spawns a task that aborts the task of `shared` when its deadline elapses.
+///
+/// The returned sender updates the deadline, and the watcher stops when it is dropped along with the shared state.
+fn watch_deadline(shared: Weak<Shared<AbortHandle>>) -> watch::Sender<Option<Deadline>> {
+    let (sender, mut receiver) = watch::channel::<Option<Deadline>>(None);
+    tokio::spawn(async move {
+        let reason = loop {
+            let deadline = *receiver.borrow_and_update();
+            let changed = match deadline {
+                Some(deadline) => match timeout_at(deadline.at, receiver.changed()).await {
+                    Ok(changed) => changed,
+                    Err(_) => break deadline.reason(),
+                },
+                None => receiver.changed().await,
+            };
+            if changed.is_err() {
+                return;
+            }
+        };
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        if !shared.abort_handle.is_finished() {
+            debug!(
+                task.id = %shared.task_id(),
+                name = shared.name(),
+                reason = %reason,
+                "deadline elapsed: abort task"
+            );
+            shared.abort(reason);
+        }
+    });
+    sender
+}
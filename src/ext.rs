@@ -0,0 +1,55 @@
+use crate::{DropHandle, DropHandleGroup, DropJoinHandle};
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
+
+/// An extension trait to wrap Tokio handles into their drop-aborting counterpart.
+///
+/// This avoids the type annotation required by the `From` conversions, and makes the ownership intent explicit at the call site.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::AbortOnDropExt;
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_join_handle = tokio::spawn(async {
+///         sleep(Duration::from_millis(100)).await;
+///         42
+///     })
+///     .abort_on_drop();
+///     assert_eq!(drop_join_handle.await.unwrap(), 42);
+/// }
+/// ```
+pub trait AbortOnDropExt {
+    /// The wrapper aborting the task(s) when dropped.
+    type Handle;
+
+    /// Wraps the handle so the task(s) will be aborted when the returned wrapper is dropped.
+    fn abort_on_drop(self) -> Self::Handle;
+}
+
+impl<T> AbortOnDropExt for JoinHandle<T> {
+    type Handle = DropJoinHandle<T>;
+
+    #[track_caller]
+    fn abort_on_drop(self) -> DropJoinHandle<T> {
+        self.into()
+    }
+}
+
+impl AbortOnDropExt for AbortHandle {
+    type Handle = DropHandle;
+
+    #[track_caller]
+    fn abort_on_drop(self) -> DropHandle {
+        self.into()
+    }
+}
+
+impl<T> AbortOnDropExt for JoinSet<T> {
+    type Handle = DropHandleGroup<T>;
+
+    fn abort_on_drop(self) -> DropHandleGroup<T> {
+        self.into()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    DropHandle,
+    hooks::TaskHooks,
+    shared::{OnAbort, Shared},
+};
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{runtime::Handle, task::AbortHandle};
+use tokio_util::sync::CancellationToken;
+
+/// Spawns a task that is shut down gracefully when the last `DropHandle` is dropped.
+///
+/// The task is given a `CancellationToken`. When the last `DropHandle` is dropped, the token is cancelled so the task can flush or close its resources,
+/// and the task is aborted if it is still running once `grace_period` has elapsed.
+///
+/// The grace period is waited on the runtime the task was spawned on, which must have the time driver enabled.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::{DropHandle, spawn_graceful};
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle: DropHandle = spawn_graceful(Duration::from_secs(5), |token| async move {
+///         while !token.is_cancelled() {
+///             println!("Task is running...");
+///             sleep(Duration::from_secs(1)).await;
+///         }
+///         println!("Task is shutting down...");
+///     });
+///     // The task will be cancelled when `drop_handle` goes out of scope, and aborted 5 seconds later if still running.
+/// }
+/// ```
+#[track_caller]
+pub fn spawn_graceful<F, Fut>(grace_period: Duration, task: F) -> DropHandle
+where
+    F: FnOnce(CancellationToken) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let runtime = Handle::current();
+    let token = CancellationToken::new();
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = runtime
+        .spawn(task_hooks.wrap(task(token.clone())))
+        .abort_handle();
+    let graceful = GracefulShutdown {
+        abort_handle: abort_handle.clone(),
+        token,
+        grace_period,
+        runtime,
+    };
+    DropHandle::new(
+        Shared::new(abort_handle)
+            .with_graceful(graceful)
+            .with_task_hooks(task_hooks),
+    )
+}
+
+/// How to shut down a task when its last `DropHandle` is dropped.
+#[derive(Debug)]
+pub struct GracefulShutdown {
+    abort_handle: AbortHandle,
+    token: CancellationToken,
+    grace_period: Duration,
+    runtime: Handle,
+}
+
+impl GracefulShutdown {
+    /// Cancels the token, then aborts the task named `name` once the grace period has elapsed.
+    ///
+    /// The `on_abort` callbacks are only run if the task is aborted, not if it completes within the grace period.
+    pub fn shutdown(&self, name: Option<&str>, on_abort: Vec<OnAbort>) {
+        self.token.cancel();
+        let abort_handle = self.abort_handle.clone();
+        let grace_period = self.grace_period;
+        let name = name.map(str::to_owned);
+        self.runtime.spawn(async move {
+            tokio::time::sleep(grace_period).await;
+            if !abort_handle.is_finished() {
+                debug!(
+                    task.id = %abort_handle.id(),
+                    name = name.as_deref(),
+                    "grace period elapsed: abort task"
+                );
+                abort_handle.abort();
+                for callback in on_abort {
+                    callback();
+                }
+            }
+        });
+    }
+}
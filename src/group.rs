@@ -0,0 +1,239 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use tokio::task::{AbortHandle, JoinError, JoinHandle, JoinSet};
+
+/// An owning set of tasks that are all aborted when the group is dropped.
+///
+/// Tasks can be inserted from their `JoinHandle` or their `AbortHandle`, the same way a `DropHandle` is created,
+/// and a group can be created from a `JoinSet`.
+/// Only the tasks spawned with `spawn()`, inserted from their `JoinHandle` or coming from the `JoinSet` can be joined with `join_next()`,
+/// the others are removed from the group automatically once they are finished.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::DropHandleGroup;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut group = DropHandleGroup::new();
+///     for i in 0..10 {
+///         group.insert(tokio::spawn(async move { i * 2 }));
+///     }
+///     while let Some(result) = group.join_next().await {
+///         println!("Task returned {}", result.unwrap());
+///     }
+///     // The remaining tasks will be automatically aborted when `group` goes out of scope.
+/// }
+/// ```
+pub struct DropHandleGroup<T = ()> {
+    join_set: JoinSet<T>,
+    join_handles: Vec<JoinHandle<T>>,
+    /// The outputs of the finished `JoinHandle`s, taken out of them so their tasks are released before being joined.
+    finished: VecDeque<Result<T, JoinError>>,
+    abort_handles: Vec<AbortHandle>,
+}
+
+/// A task that can be inserted into a `DropHandleGroup`.
+///
+/// It is created from a `JoinHandle<T>` or an `AbortHandle`.
+pub struct GroupMember<T>(Member<T>);
+
+enum Member<T> {
+    Join(JoinHandle<T>),
+    Abort(AbortHandle),
+}
+
+impl<T> From<JoinHandle<T>> for GroupMember<T> {
+    fn from(value: JoinHandle<T>) -> Self {
+        Self(Member::Join(value))
+    }
+}
+
+impl<T> From<AbortHandle> for GroupMember<T> {
+    fn from(value: AbortHandle) -> Self {
+        Self(Member::Abort(value))
+    }
+}
+
+impl<T> DropHandleGroup<T> {
+    /// Creates an empty group.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            join_set: JoinSet::new(),
+            join_handles: Vec::new(),
+            finished: VecDeque::new(),
+            abort_handles: Vec::new(),
+        }
+    }
+
+    /// Inserts a task into the group, from its `JoinHandle` or its `AbortHandle`.
+    ///
+    /// The finished tasks that cannot be joined are removed from the group,
+    /// and the finished tasks that can be joined are released, keeping only their output until it is joined.
+    pub fn insert(&mut self, handle: impl Into<GroupMember<T>>) {
+        self.reap();
+        match handle.into().0 {
+            Member::Join(join_handle) => self.join_handles.push(join_handle),
+            Member::Abort(abort_handle) => self.abort_handles.push(abort_handle),
+        }
+    }
+
+    /// Returns the number of tasks of the group.
+    ///
+    /// The tasks that can be joined are counted until they are joined, the others until they are finished.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.join_set.len()
+            + self.join_handles.len()
+            + self.finished.len()
+            + self
+                .abort_handles
+                .iter()
+                .filter(|handle| !handle.is_finished())
+                .count()
+    }
+
+    /// Returns `true` if the group has no task left, see `len()`.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the finished tasks that cannot be joined, and takes the output of the finished `JoinHandle`s.
+    fn reap(&mut self) {
+        self.abort_handles.retain(|handle| !handle.is_finished());
+        // A finished `JoinHandle` is ready, so it does not need to be woken
+        let mut cx = Context::from_waker(Waker::noop());
+        self.join_handles.retain_mut(|join_handle| {
+            if !join_handle.is_finished() {
+                return true;
+            }
+            match Pin::new(join_handle).poll(&mut cx) {
+                Poll::Ready(result) => {
+                    self.finished.push_back(result);
+                    false
+                }
+                Poll::Pending => true,
+            }
+        });
+    }
+
+    /// Aborts every task that is not part of the `JoinSet`, which aborts its own tasks when dropped.
+    fn abort_handles(&mut self) {
+        for join_handle in &self.join_handles {
+            join_handle.abort();
+        }
+        for abort_handle in self.abort_handles.drain(..) {
+            abort_handle.abort();
+        }
+    }
+}
+
+impl<T: 'static> DropHandleGroup<T> {
+    /// Spawns a task on the current Tokio runtime and inserts it into the group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send,
+    {
+        self.join_set.spawn(future);
+    }
+
+    /// Waits until one of the tasks that can be joined finishes, and returns its output.
+    ///
+    /// Returns `None` if the group has no task left to join.
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        poll_fn(|cx| self.poll_join_next(cx)).await
+    }
+
+    /// Polls for one of the tasks that can be joined to finish.
+    ///
+    /// Returns `Poll::Ready(None)` if the group has no task left to join.
+    pub fn poll_join_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, JoinError>>> {
+        if let Some(result) = self.finished.pop_front() {
+            return Poll::Ready(Some(result));
+        }
+        if let Poll::Ready(Some(result)) = self.join_set.poll_join_next(cx) {
+            return Poll::Ready(Some(result));
+        }
+        if self.join_handles.is_empty() && self.join_set.is_empty() {
+            return Poll::Ready(None);
+        }
+        for index in 0..self.join_handles.len() {
+            if let Poll::Ready(result) = Pin::new(&mut self.join_handles[index]).poll(cx) {
+                self.join_handles.swap_remove(index);
+                return Poll::Ready(Some(result));
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Aborts every task of the group.
+    ///
+    /// The aborted tasks can still be joined with `join_next()`, which will return a cancelled `JoinError`.
+    pub fn abort_all(&mut self) {
+        self.join_set.abort_all();
+        self.abort_handles();
+    }
+}
+
+impl<T> Default for DropHandleGroup<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for DropHandleGroup<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropHandleGroup")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> From<JoinSet<T>> for DropHandleGroup<T> {
+    fn from(value: JoinSet<T>) -> Self {
+        Self {
+            join_set: value,
+            join_handles: Vec::new(),
+            finished: VecDeque::new(),
+            abort_handles: Vec::new(),
+        }
+    }
+}
+
+impl<T, H: Into<GroupMember<T>>> Extend<H> for DropHandleGroup<T> {
+    fn extend<I: IntoIterator<Item = H>>(&mut self, iter: I) {
+        for handle in iter {
+            self.insert(handle);
+        }
+    }
+}
+
+impl<T, H: Into<GroupMember<T>>> FromIterator<H> for DropHandleGroup<T> {
+    fn from_iter<I: IntoIterator<Item = H>>(iter: I) -> Self {
+        let mut group = Self::new();
+        group.extend(iter);
+        group
+    }
+}
+
+/// When the `DropHandleGroup` is dropped, every task of the group will be aborted.
+///
+/// The tasks coming from a `JoinSet` are aborted when the `JoinSet` itself is dropped.
+impl<T> Drop for DropHandleGroup<T> {
+    fn drop(&mut self) {
+        debug!(tasks = self.len(), "drop DropHandleGroup: abort tasks");
+        self.abort_handles();
+    }
+}
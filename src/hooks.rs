@@ -0,0 +1,269 @@
+use crate::{
+    PanicPolicy,
+    panic::{PanicSink, panic_message},
+};
+use std::{
+    any::Any,
+    fmt,
+    future::{Future, poll_fn},
+    mem,
+    panic::{self, AssertUnwindSafe},
+    pin::pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::Poll,
+};
+use tokio::{
+    sync::oneshot,
+    task::{Id, JoinHandle},
+};
+
+/// How a task terminated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskOutcome {
+    /// The task ran to completion.
+    Completed,
+    /// The task was aborted, or its runtime was shut down, before it could complete.
+    Cancelled,
+    /// The task panicked.
+    Panicked,
+}
+
+type OnFinish = Box<dyn FnOnce(TaskOutcome) + Send>;
+type PanicPayload = Box<dyn Any + Send>;
+
+/// The `on_finish` callbacks and the panic policy of a task spawned through this crate, applied from inside the task when it terminates,
+/// or of a task converted from its `JoinHandle`, applied by a watcher task awaiting it.
+#[derive(Default)]
+pub struct TaskHooks {
+    finish: Mutex<FinishState>,
+    panic: Mutex<PanicState>,
+}
+
+#[derive(Default)]
+enum FinishState {
+    #[default]
+    Running,
+    Pending(Vec<OnFinish>),
+    Finished(TaskOutcome),
+}
+
+#[derive(Default)]
+struct PanicState {
+    /// The panic policy of the task, `None` until it is set.
+    policy: Option<PanicPolicy>,
+    /// The payload of the task panic, kept until a policy consumes it.
+    payload: Option<(Id, PanicPayload)>,
+}
+
+impl TaskHooks {
+    /// Registers a callback, called right away if the task has already terminated.
+    pub fn on_finish(&self, callback: OnFinish) {
+        let mut state = self.finish.lock().unwrap_or_else(PoisonError::into_inner);
+        match &mut *state {
+            FinishState::Running => *state = FinishState::Pending(vec![callback]),
+            FinishState::Pending(callbacks) => callbacks.push(callback),
+            FinishState::Finished(outcome) => {
+                let outcome = *outcome;
+                drop(state);
+                callback(outcome);
+            }
+        }
+    }
+
+    /// Returns how the task terminated, if it did.
+    pub fn outcome(&self) -> Option<TaskOutcome> {
+        match &*self.finish.lock().unwrap_or_else(PoisonError::into_inner) {
+            FinishState::Finished(outcome) => Some(*outcome),
+            FinishState::Running | FinishState::Pending(_) => None,
+        }
+    }
+
+    /// Waits for the task to terminate, and returns how it terminated.
+    pub async fn wait(&self) -> TaskOutcome {
+        let (sender, receiver) = oneshot::channel();
+        self.on_finish(Box::new(move |outcome| {
+            let _ = sender.send(outcome);
+        }));
+        // The callback is only dropped without being called if the task is leaked, which never terminates
+        receiver.await.unwrap_or(TaskOutcome::Cancelled)
+    }
+
+    /// Sets the panic policy, applied right away if the task has already panicked.
+    pub fn set_panic_policy(&self, policy: PanicPolicy) {
+        let payload = {
+            let mut state = self.panic.lock().unwrap_or_else(PoisonError::into_inner);
+            state.policy = Some(policy);
+            state.payload.take()
+        };
+        if let Some((id, payload)) = payload {
+            drop(self.panicked(id, payload));
+        }
+    }
+
+    /// Takes the panic payload of the task, if it panicked and is to be re-raised by `PanicPolicy::Resume`.
+    pub fn take_resumable_panic(&self) -> Option<PanicPayload> {
+        let mut state = self.panic.lock().unwrap_or_else(PoisonError::into_inner);
+        if matches!(state.policy, Some(PanicPolicy::Resume)) {
+            state.payload.take().map(|(_, payload)| payload)
+        } else {
+            None
+        }
+    }
+
+    /// Applies the panic policy to the payload, returning it back if the policy did not consume it.
+    fn panicked(&self, id: Id, payload: PanicPayload) -> Option<PanicPayload> {
+        let mut state = self.panic.lock().unwrap_or_else(PoisonError::into_inner);
+        match &state.policy {
+            Some(PanicPolicy::Ignore) => Some(payload),
+            Some(PanicPolicy::Log) => {
+                error!(
+                    task.id = %id,
+                    panic = panic_message(&*payload).unwrap_or("Box<dyn Any>"),
+                    "task panicked"
+                );
+                Some(payload)
+            }
+            Some(PanicPolicy::Sink(sink)) => {
+                let sink: PanicSink = sink.clone();
+                drop(state);
+                sink(id, payload);
+                None
+            }
+            Some(PanicPolicy::Resume) | None => {
+                state.payload = Some((id, payload));
+                None
+            }
+        }
+    }
+
+    fn finish(&self, outcome: TaskOutcome) {
+        let state = mem::replace(
+            &mut *self.finish.lock().unwrap_or_else(PoisonError::into_inner),
+            FinishState::Finished(outcome),
+        );
+        if let FinishState::Pending(callbacks) = state {
+            for callback in callbacks {
+                callback(outcome);
+            }
+        }
+    }
+
+    /// Wraps a future so the callbacks are called when it completes, panics, or is dropped before completion.
+    pub fn wrap<F: Future>(
+        self: &Arc<Self>,
+        future: F,
+    ) -> impl Future<Output = F::Output> + use<F> {
+        let guard = FinishGuard::new(self.clone());
+        async move {
+            // Bind the guard first so it is dropped last, once the future and its resources are dropped
+            let guard = guard;
+            let mut future = pin!(future);
+            let output = poll_fn(|cx| {
+                match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                    Ok(poll) => poll.map(Ok),
+                    Err(payload) => Poll::Ready(Err(payload)),
+                }
+            })
+            .await;
+            match output {
+                Ok(output) => {
+                    guard.complete();
+                    output
+                }
+                Err(payload) => guard.panicked(payload),
+            }
+        }
+    }
+
+    /// Wraps a blocking function so the callbacks are called when it returns, panics, or is dropped before running.
+    pub fn wrap_blocking<F: FnOnce() -> R, R>(
+        self: &Arc<Self>,
+        f: F,
+    ) -> impl FnOnce() -> R + use<F, R> {
+        let guard = FinishGuard::new(self.clone());
+        move || match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(output) => {
+                guard.complete();
+                output
+            }
+            Err(payload) => guard.panicked(payload),
+        }
+    }
+
+    /// Awaits the `JoinHandle` of a task so the callbacks are called when it terminates, to be spawned as a watcher task.
+    ///
+    /// If the watcher is dropped before the task terminates, e.g. by the shutdown of its runtime, the task is reported as cancelled.
+    pub fn watch<T>(
+        self: &Arc<Self>,
+        join_handle: JoinHandle<T>,
+    ) -> impl Future<Output = ()> + use<T> {
+        let guard = FinishGuard::new(self.clone());
+        async move {
+            let guard = guard;
+            match join_handle.await {
+                Ok(_) => guard.complete(),
+                Err(error) if error.is_panic() => {
+                    let id = error.id();
+                    drop(guard.hooks.panicked(id, error.into_panic()));
+                    guard.finish(TaskOutcome::Panicked);
+                }
+                Err(_) => guard.finish(TaskOutcome::Cancelled),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TaskHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskHooks")
+            .field("outcome", &self.outcome())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reports the outcome of the task when dropped, i.e. when the task terminates.
+struct FinishGuard {
+    hooks: Arc<TaskHooks>,
+    /// The outcome to report, unless the task is panicking.
+    outcome: TaskOutcome,
+}
+
+impl FinishGuard {
+    /// Creates a guard reporting the task as cancelled, unless it completes or panics first.
+    const fn new(hooks: Arc<TaskHooks>) -> Self {
+        Self {
+            hooks,
+            outcome: TaskOutcome::Cancelled,
+        }
+    }
+
+    /// Reports that the task ran to completion.
+    fn complete(self) {
+        self.finish(TaskOutcome::Completed);
+    }
+
+    /// Reports how the task terminated.
+    fn finish(mut self, outcome: TaskOutcome) {
+        self.outcome = outcome;
+    }
+
+    /// Applies the panic policy, then resumes the panic so the task terminates as panicked.
+    fn panicked(self, payload: PanicPayload) -> ! {
+        let payload = self
+            .hooks
+            .panicked(tokio::task::id(), payload)
+            .unwrap_or_else(|| Box::new("task panicked"));
+        panic::resume_unwind(payload)
+    }
+}
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        let outcome = if self.outcome == TaskOutcome::Cancelled && std::thread::panicking() {
+            TaskOutcome::Panicked
+        } else {
+            self.outcome
+        };
+        self.hooks.finish(outcome);
+    }
+}
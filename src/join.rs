@@ -0,0 +1,79 @@
+use crate::DropHandle;
+use std::{
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::task::{AbortHandle, JoinError, JoinHandle};
+
+/// A handle that aborts the task when dropped, and that can be awaited for the task's output.
+///
+/// Unlike `DropHandle`, a `DropJoinHandle<T>` keeps the join half of the task, so awaiting it resolves to the task's result.
+/// The task will only be aborted when the `DropJoinHandle` and every `DropHandle` obtained from it are dropped.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::DropJoinHandle;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_join_handle: DropJoinHandle<u32> = tokio::spawn(async { 42 }).into();
+///     assert_eq!(drop_join_handle.await.unwrap(), 42);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DropJoinHandle<T> {
+    join_handle: JoinHandle<T>,
+    drop_handle: DropHandle,
+}
+
+impl<T> DropJoinHandle<T> {
+    /// Aborts the task, and recursively every child task, see `DropHandle::abort()`.
+    ///
+    /// This shadows the `abort()` method of the underlying `AbortHandle`, which only aborts the task itself.
+    pub fn abort(&self) {
+        self.drop_handle.abort();
+    }
+
+    /// Returns a `DropHandle` sharing the ownership of the task.
+    ///
+    /// The task will keep running as long as this `DropJoinHandle` or any of the returned `DropHandle` is alive.
+    #[must_use]
+    pub fn drop_handle(&self) -> DropHandle {
+        self.drop_handle.clone()
+    }
+}
+
+impl<T> Deref for DropJoinHandle<T> {
+    type Target = AbortHandle;
+
+    fn deref(&self) -> &AbortHandle {
+        &self.drop_handle
+    }
+}
+
+impl<T> From<JoinHandle<T>> for DropJoinHandle<T> {
+    #[track_caller]
+    fn from(value: JoinHandle<T>) -> Self {
+        Self {
+            drop_handle: value.abort_handle().into(),
+            join_handle: value,
+        }
+    }
+}
+
+/// Drops the join half, keeping only the abort side of the task.
+impl<T> From<DropJoinHandle<T>> for DropHandle {
+    fn from(value: DropJoinHandle<T>) -> Self {
+        value.drop_handle
+    }
+}
+
+impl<T> Future for DropJoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.join_handle).poll(cx)
+    }
+}
@@ -21,12 +21,94 @@
 //! }
 //! ```
 
-use std::{ops::Deref, sync::Arc};
-use tokio::task::{AbortHandle, JoinHandle};
-use tracing::{debug, trace};
+#[cfg(feature = "tokio")]
+use crate::{hooks::TaskHooks, shared::Shared};
+#[cfg(feature = "tokio")]
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    panic::resume_unwind,
+    time::Duration,
+};
+use std::{borrow::Cow, fmt, ops::Deref, panic::Location, sync::Arc};
+#[cfg(feature = "tokio")]
+use tokio::task::{AbortHandle, Id, JoinHandle};
 
-#[cfg(test)]
+// Declared first, so its logging macros are available to the other modules
+#[macro_use]
+mod logging;
+
+mod abortable;
+mod children;
+#[cfg(feature = "tokio")]
+mod deadline;
+#[cfg(feature = "tokio")]
+mod ext;
+#[cfg(feature = "tokio")]
+mod graceful;
+#[cfg(feature = "tokio")]
+mod group;
+#[cfg(feature = "tokio")]
+mod hooks;
+#[cfg(feature = "tokio")]
+mod join;
+#[cfg(feature = "tokio")]
+mod local;
+#[cfg(all(test, drop_handle_loom))]
+mod loom_tests;
+#[cfg(feature = "tokio")]
+mod panic;
+mod reason;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "tokio")]
+mod scope;
+mod shared;
+#[cfg(feature = "tokio")]
+mod spawn;
+#[cfg(feature = "tokio")]
+mod supervise;
+#[cfg(all(test, feature = "tokio", not(drop_handle_loom)))]
 mod tests;
+#[cfg(feature = "tokio")]
+mod unique;
+#[cfg(feature = "tokio")]
+mod weak;
+
+pub use abortable::Abortable;
+#[cfg(feature = "async-task")]
+pub use abortable::AsyncTaskHandle;
+#[cfg(feature = "tokio")]
+pub use ext::AbortOnDropExt;
+#[cfg(feature = "tokio")]
+pub use graceful::spawn_graceful;
+#[cfg(feature = "tokio")]
+pub use group::{DropHandleGroup, GroupMember};
+#[cfg(feature = "tokio")]
+pub use hooks::TaskOutcome;
+#[cfg(feature = "tokio")]
+pub use join::DropJoinHandle;
+#[cfg(feature = "tokio")]
+pub use local::LocalDropHandle;
+pub use logging::{LogLevel, log_level, set_log_level};
+#[cfg(feature = "tokio")]
+pub use panic::{PanicPolicy, PanicSink};
+pub use reason::AbortReason;
+#[cfg(feature = "tokio")]
+pub use scope::{Scope, ScopeExit, scope, scope_with};
+#[cfg(all(feature = "tokio", feature = "tracing"))]
+pub use spawn::spawn_instrumented;
+#[cfg(feature = "tokio")]
+pub use spawn::{spawn, spawn_blocking, spawn_local, spawn_named, spawn_on, spawn_with_timeout};
+#[cfg(feature = "tokio")]
+pub use supervise::{RestartPolicy, Supervisor, SupervisorError, supervise, supervise_with};
+#[cfg(feature = "tokio")]
+pub use tokio_util::sync::CancellationToken;
+#[cfg(feature = "tokio")]
+pub use unique::UniqueDropHandle;
+#[cfg(feature = "tokio")]
+pub use weak::WeakDropHandle;
 
 /// A handle that aborts the task when dropped.
 ///
@@ -50,38 +132,557 @@ mod tests;
 ///     // The task will be automatically aborted when `drop_handle` goes out of scope.
 /// }
 /// ```
-#[derive(Clone, Debug)]
-pub struct DropHandle(Arc<AbortHandle>);
+///
+/// By default, a `DropHandle` holds a Tokio task, but it can hold any `Abortable` task handle, e.g. `DropHandle<futures::future::AbortHandle>`.
+#[cfg(feature = "tokio")]
+pub struct DropHandle<A: Abortable = AbortHandle>(Arc<shared::Shared<A>>);
+
+/// A handle that aborts the task when dropped.
+///
+/// The task will only be aborted when the last `DropHandle` is dropped, so you can clone it to keep the task alive.
+/// It can hold any `Abortable` task handle, e.g. `DropHandle<futures::future::AbortHandle>`.
+#[cfg(not(feature = "tokio"))]
+pub struct DropHandle<A: Abortable>(Arc<shared::Shared<A>>);
+
+#[cfg(feature = "tokio")]
+impl DropHandle {
+    /// Creates a `DropHandle` from the `JoinHandle` of a task, watching its termination.
+    ///
+    /// Unlike the `From<JoinHandle<T>>` conversion, a watcher task awaiting the `JoinHandle` is spawned on the current runtime,
+    /// so `on_finish()`, `set_panic_policy()` and `wait()` are available, as for the tasks spawned through this crate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::{DropHandle, TaskOutcome};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = DropHandle::watch(tokio::spawn(async {}));
+    ///     assert_eq!(drop_handle.wait().await, Some(TaskOutcome::Completed));
+    /// }
+    /// ```
+    #[track_caller]
+    #[must_use]
+    pub fn watch<T: Send + 'static>(join_handle: JoinHandle<T>) -> Self {
+        let task_hooks = Arc::new(TaskHooks::default());
+        let shared = Shared::new(join_handle.abort_handle()).with_task_hooks(task_hooks.clone());
+        tokio::spawn(task_hooks.watch(join_handle));
+        Self::new(shared)
+    }
+
+    /// Creates a `WeakDropHandle` observing the task, without keeping it alive.
+    #[must_use]
+    pub fn downgrade(&self) -> WeakDropHandle {
+        WeakDropHandle::new(self)
+    }
+
+    /// Registers a callback to run when the task terminates, with how it terminated.
+    ///
+    /// The callback is run right away if the task has already terminated.
+    /// This is only available for the tasks spawned through this crate, e.g. with `drop_handle::spawn`,
+    /// or created with `DropHandle::watch()`: for the tasks converted from their `JoinHandle` or their `AbortHandle`,
+    /// the callback is dropped without being run.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::TaskOutcome;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(async {});
+    ///     drop_handle.on_finish(|outcome| assert_eq!(outcome, TaskOutcome::Completed));
+    /// }
+    /// ```
+    pub fn on_finish(&self, callback: impl FnOnce(TaskOutcome) + Send + 'static) {
+        if let Some(task_hooks) = &self.0.task_hooks {
+            task_hooks.on_finish(Box::new(callback));
+        } else {
+            warn!(
+                task.id = %self.0.task_id(),
+                name = self.0.name(),
+                "on_finish: the task is not watched, the callback will never run"
+            );
+        }
+    }
+
+    /// Sets what to do if the task panics, applied right away if it has already panicked.
+    ///
+    /// Until a policy is set, the panic payload is kept, so setting the policy right after spawning the task never misses a panic.
+    /// This is only available for the tasks whose termination is watched, see `on_finish()`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::PanicPolicy;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(async { panic!("boom") });
+    ///     drop_handle.set_panic_policy(PanicPolicy::sink(|id, _payload| {
+    ///         eprintln!("task {id} panicked");
+    ///     }));
+    /// }
+    /// ```
+    pub fn set_panic_policy(&self, policy: PanicPolicy) {
+        if let Some(task_hooks) = &self.0.task_hooks {
+            task_hooks.set_panic_policy(policy);
+        } else {
+            warn!(
+                task.id = %self.0.task_id(),
+                name = self.0.name(),
+                "set_panic_policy: the task is not watched, the policy will never apply"
+            );
+        }
+    }
+
+    /// Aborts the task like `abort()`, and waits for it to terminate.
+    ///
+    /// Returns how the task terminated: it may have completed or panicked before being aborted.
+    /// The outcome is only known for the tasks whose termination is watched, see `on_finish()`:
+    /// for the tasks converted from their `JoinHandle` or their `AbortHandle`, this returns `None` once the task has terminated.
+    ///
+    /// # Panics
+    ///
+    /// Re-raises the panic of the task if it panicked under `PanicPolicy::Resume`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::TaskOutcome;
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>());
+    ///     let outcome = drop_handle.abort_and_wait().await;
+    ///     assert_eq!(outcome, Some(TaskOutcome::Cancelled));
+    /// }
+    /// ```
+    pub async fn abort_and_wait(&self) -> Option<TaskOutcome> {
+        self.abort();
+        self.wait().await
+    }
 
-impl Deref for DropHandle {
-    type Target = AbortHandle;
+    /// Shuts the task down, and waits for it to terminate.
+    ///
+    /// The task is aborted like with `abort_and_wait()`, or shut down gracefully if it was spawned with `spawn_graceful`.
+    /// Returns how the task terminated, if it is known.
+    ///
+    /// # Panics
+    ///
+    /// Re-raises the panic of the task if it panicked under `PanicPolicy::Resume`.
+    pub async fn shutdown(self) -> Option<TaskOutcome> {
+        if let Some(graceful) = &self.0.graceful {
+            debug!(
+                task.id = %self.0.task_id(),
+                name = self.0.name(),
+                reason = %AbortReason::Explicit,
+                "shut down task"
+            );
+            self.0.record_abort_reason(AbortReason::Explicit);
+            graceful.shutdown(self.0.name(), Vec::new());
+            self.0.abort_children();
+        } else {
+            self.abort();
+        }
+        self.wait().await
+    }
 
-    fn deref(&self) -> &AbortHandle {
-        &self.0
+    /// Waits for the task to terminate, without aborting it, and returns how it terminated.
+    ///
+    /// The outcome is only known for the tasks whose termination is watched, see `abort_and_wait()`.
+    /// The termination of the other tasks can only be polled, with a backoff, which requires the time driver of the runtime.
+    ///
+    /// # Panics
+    ///
+    /// Re-raises the panic of the task if it panicked under `PanicPolicy::Resume`.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::{DropHandle, TaskOutcome};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(async {});
+    ///     assert_eq!(drop_handle.wait().await, Some(TaskOutcome::Completed));
+    ///     // The termination of a task converted from its `JoinHandle` is not watched
+    ///     let drop_handle: DropHandle = tokio::spawn(async {}).into();
+    ///     assert_eq!(drop_handle.wait().await, None);
+    /// }
+    /// ```
+    pub async fn wait(&self) -> Option<TaskOutcome> {
+        if let Some(task_hooks) = &self.0.task_hooks {
+            let outcome = task_hooks.wait().await;
+            if let Some(payload) = task_hooks.take_resumable_panic() {
+                resume_unwind(payload);
+            }
+            return Some(outcome);
+        }
+        // Without the hooks of this crate, the termination of the task can only be polled
+        let mut backoff = Duration::from_millis(1);
+        while !self.0.abort_handle.is_finished() {
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(Duration::from_millis(100));
+        }
+        None
     }
 }
 
+impl<A: Abortable> DropHandle<A> {
+    /// Creates a `DropHandle` from any `Abortable` task handle.
+    ///
+    /// Prefer the `From` conversions for the task handles this crate supports, e.g. `From<tokio::task::AbortHandle>`.
+    /// A Tokio task is registered when the `registry` feature is enabled, like with the `From` conversions.
+    #[track_caller]
+    pub fn from_abortable(abort_handle: A) -> Self {
+        Self::new(shared::Shared::new(abort_handle))
+    }
+
+    /// Creates a `DropHandle` from its shared state, registering it if it holds a Tokio task and the `registry` feature is enabled.
+    #[track_caller]
+    pub(crate) fn new(shared: shared::Shared<A>) -> Self {
+        #[cfg(feature = "registry")]
+        let shared = if shared.id.is_some() {
+            registry::register(shared)
+        } else {
+            Arc::new(shared)
+        };
+        #[cfg(not(feature = "registry"))]
+        let shared = Arc::new(shared);
+        debug!(
+            task.id = %shared.task_id(),
+            name = shared.name(),
+            location = %Location::caller(),
+            "create DropHandle"
+        );
+        Self(shared)
+    }
+
+    /// Aborts the task, and recursively every child task attached with `attach_child()` or spawned with `spawn_child()`.
+    ///
+    /// This shadows the `abort()` method of the underlying task handle, which only aborts the task itself,
+    /// and records `AbortReason::Explicit` as the reason.
+    pub fn abort(&self) {
+        self.abort_with(AbortReason::Explicit);
+    }
+
+    /// Aborts the task like `abort()`, recording the given reason.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::AbortReason;
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>());
+    ///     drop_handle.abort_with(AbortReason::custom("client disconnected"));
+    ///     assert_eq!(
+    ///         drop_handle.abort_reason(),
+    ///         Some(AbortReason::custom("client disconnected"))
+    ///     );
+    /// }
+    /// ```
+    pub fn abort_with(&self, reason: AbortReason) {
+        debug!(task.id = %self.0.task_id(), name = self.0.name(), reason = %reason, "abort task");
+        self.0.abort(reason);
+    }
+
+    /// Returns why the task was aborted through its `DropHandle`, if it was.
+    ///
+    /// Returns `None` if the task is still running, ran to completion, or was aborted by other means,
+    /// e.g. through a detached `AbortHandle` or by the shutdown of its runtime.
+    #[must_use]
+    pub fn abort_reason(&self) -> Option<AbortReason> {
+        self.0.abort_reason()
+    }
+
+    /// Names the task, and returns this `DropHandle`.
+    ///
+    /// The name is shared by every clone of this `DropHandle`, shown by its `Debug` and `Display` implementations,
+    /// and emitted in every event about the task. Only the first name is kept: naming a task that is already named,
+    /// e.g. spawned with `spawn_named()`, does not change it.
+    ///
+    /// Example usage:
+    /// ```
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>()).with_name("heartbeat");
+    ///     assert_eq!(drop_handle.name(), Some("heartbeat"));
+    ///     assert_eq!(
+    ///         drop_handle.to_string(),
+    ///         format!("heartbeat (task {})", drop_handle.id())
+    ///     );
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_name(self, name: impl Into<Cow<'static, str>>) -> Self {
+        let name = name.into();
+        if self.0.set_name(name.clone()) {
+            debug!(task.id = %self.0.task_id(), %name, "name DropHandle");
+        }
+        self
+    }
+
+    /// Returns the name of the task, if it is named.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
+
+    /// Detaches the task, so it keeps running after the last `DropHandle` is dropped.
+    ///
+    /// This disarms every clone of this `DropHandle`, and returns the underlying task handle, e.g. the Tokio `AbortHandle`.
+    /// The child tasks stay attached, and are detached too once the last `DropHandle` of this task is dropped.
+    ///
+    /// Example usage:
+    /// ```
+    /// use drop_handle::DropHandle;
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle: DropHandle = tokio::spawn(pending::<()>()).into();
+    ///     let abort_handle = drop_handle.detach();
+    ///     // The task is still running, and can only be aborted through `abort_handle`.
+    ///     assert!(!abort_handle.is_finished());
+    /// }
+    /// ```
+    #[must_use = "the task can no longer be aborted if the returned handle is dropped"]
+    pub fn detach(self) -> A
+    where
+        A: Clone,
+    {
+        debug!(task.id = %self.0.task_id(), name = self.0.name(), "detach DropHandle");
+        self.0.disarm();
+        self.0.abort_handle.clone()
+    }
+
+    /// Returns `true` if the task will be aborted when the last `DropHandle` is dropped, i.e. it has not been detached.
+    #[must_use]
+    pub fn is_armed(&self) -> bool {
+        self.0.is_armed()
+    }
+
+    /// Registers a callback to run when the last `DropHandle` is dropped and aborts the task.
+    ///
+    /// The callback is not run if the task is detached, was already aborted, e.g. explicitly with `abort()` or `shutdown()`,
+    /// or has already finished, when its task handle can tell, see `Abortable::is_finished()`.
+    /// For a task spawned with `spawn_graceful`, it is only run if the task is aborted once the grace period has elapsed,
+    /// not if it completes within the grace period.
+    ///
+    /// Example usage:
+    /// ```
+    /// use std::future::pending;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let drop_handle = drop_handle::spawn(pending::<()>());
+    ///     drop_handle.on_abort(|| println!("Task was aborted"));
+    ///     drop(drop_handle);
+    /// }
+    /// ```
+    pub fn on_abort(&self, callback: impl FnOnce() + Send + 'static) {
+        self.0.on_abort(Box::new(callback));
+    }
+}
+
+impl<A: Abortable> Clone for DropHandle<A> {
+    fn clone(&self) -> Self {
+        self.0.holders.acquire();
+        Self(self.0.clone())
+    }
+}
+
+impl<A: Abortable> fmt::Debug for DropHandle<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropHandle")
+            .field("abort_handle", &self.0.abort_handle)
+            .field("name", &self.0.name())
+            .field("holders", &self.0.holders.count())
+            .field("armed", &self.0.is_armed())
+            .finish()
+    }
+}
+
+impl<A: Abortable> Deref for DropHandle<A> {
+    type Target = A;
+
+    fn deref(&self) -> &A {
+        &self.0.abort_handle
+    }
+}
+
+/// Displays the name of the task and its id, e.g. `worker (task 42)`, or only its id if it is not named, e.g. `task 42`.
+#[cfg(feature = "tokio")]
+impl fmt::Display for DropHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name} (task {})", self.id()),
+            None => write!(f, "task {}", self.id()),
+        }
+    }
+}
+
+/// `DropHandle`s are compared, ordered and hashed by task id, so clones of a `DropHandle` are equal.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::DropHandle;
+/// use std::{collections::HashSet, future::pending};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle = drop_handle::spawn(pending::<()>());
+///     let id = drop_handle.id();
+///     let mut drop_handles = HashSet::new();
+///     drop_handles.insert(drop_handle.clone());
+///     drop_handles.insert(drop_handle);
+///     assert_eq!(drop_handles.len(), 1);
+///     // A set of `DropHandle`s can be queried by task id
+///     assert!(drop_handles.contains(&id));
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+impl PartialEq for DropHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Eq for DropHandle {}
+
+#[cfg(feature = "tokio")]
+impl PartialOrd for DropHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Ord for DropHandle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id().cmp(&other.id())
+    }
+}
+
+/// The task id is stored when the `DropHandle` is created and never changes,
+/// so a `DropHandle` can be used as the key of a `HashSet` or a `BTreeSet`.
+/// Clippy's `mutable_key_type` lint fires on such sets because `DropHandle` has interior mutability,
+/// and can safely be allowed: none of it is hashed or compared.
+#[cfg(feature = "tokio")]
+impl Hash for DropHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Borrow<Id> for DropHandle {
+    fn borrow(&self) -> &Id {
+        self.0
+            .id
+            .as_ref()
+            .expect("the id of a Tokio task is stored when its `DropHandle` is created")
+    }
+}
+
+#[cfg(feature = "tokio")]
 impl From<AbortHandle> for DropHandle {
+    #[track_caller]
     fn from(value: AbortHandle) -> Self {
-        debug!("create DropHandle for task {:?}", value.id());
-        Self(Arc::new(value))
+        Self::new(Shared::new(value))
     }
 }
 
+/// The conversion is cheap, but the termination of the task is not watched:
+/// use `DropHandle::watch()` for `on_finish()`, `set_panic_policy()` and the outcome returned by `wait()`.
+#[cfg(feature = "tokio")]
 impl<T> From<JoinHandle<T>> for DropHandle {
+    #[track_caller]
     fn from(value: JoinHandle<T>) -> Self {
         value.abort_handle().into()
     }
 }
 
-/// When the last `DropHandle` is dropped, the task will be aborted.
-impl Drop for DropHandle {
+/// When the last `DropHandle` is dropped, the task will be aborted, or shut down gracefully if it was spawned with `spawn_graceful`.
+///
+/// Exactly one `DropHandle` observes that it is the last one, even when clones are dropped concurrently.
+/// The abort is recorded as `AbortReason::LastHandleDropped`, and the child tasks are aborted along with it.
+/// If the task has been detached, it keeps running, and so do its child tasks, which are detached too.
+/// Only the child tasks are aborted if the task was already aborted or has finished.
+impl<A: Abortable> Drop for DropHandle<A> {
     fn drop(&mut self) {
-        let drop_counter = Arc::strong_count(&self.0);
-        trace!("DropHandle counter: {}", drop_counter);
-        if drop_counter <= 1 {
-            debug!("drop DropHandle: abort task {:?}", self.0.id());
-            self.abort();
+        let drop_counter = self.0.holders.release();
+        trace!(
+            task.id = %self.0.task_id(),
+            name = self.0.name(),
+            holders = drop_counter - 1,
+            "drop DropHandle"
+        );
+        if drop_counter == 1 {
+            if !self.0.is_armed() {
+                debug!(
+                    task.id = %self.0.task_id(),
+                    name = self.0.name(),
+                    "drop DropHandle: task is detached"
+                );
+                self.0.detach_children();
+                return;
+            }
+            // The task was already aborted, shut down, or has finished: only its children are left to abort
+            if self.0.abort_reason().is_some() || self.0.is_finished() {
+                debug!(
+                    task.id = %self.0.task_id(),
+                    name = self.0.name(),
+                    "drop DropHandle: task is already terminating"
+                );
+                self.0.abort_children();
+                return;
+            }
+            let reason = AbortReason::LastHandleDropped;
+            #[cfg(feature = "tokio")]
+            if let Some(graceful) = &self.0.graceful {
+                debug!(
+                    task.id = %self.0.task_id(),
+                    name = self.0.name(),
+                    reason = %reason,
+                    "drop DropHandle: shut down task"
+                );
+                self.0.record_abort_reason(reason);
+                graceful.shutdown(self.0.name(), self.0.take_on_abort());
+                self.0.abort_children();
+                return;
+            }
+            debug!(
+                task.id = %self.0.task_id(),
+                name = self.0.name(),
+                reason = %reason,
+                "drop DropHandle: abort task"
+            );
+            self.0.abort(reason);
+            self.0.run_on_abort();
         }
     }
 }
+
+#[cfg(feature = "futures")]
+impl From<futures_util::future::AbortHandle> for DropHandle<futures_util::future::AbortHandle> {
+    #[track_caller]
+    fn from(value: futures_util::future::AbortHandle) -> Self {
+        Self::from_abortable(value)
+    }
+}
+
+#[cfg(feature = "async-task")]
+impl<T: Send + 'static> From<async_task::Task<T>> for DropHandle<AsyncTaskHandle<T>> {
+    #[track_caller]
+    fn from(value: async_task::Task<T>) -> Self {
+        Self::from_abortable(value.into())
+    }
+}
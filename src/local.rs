@@ -0,0 +1,132 @@
+use std::{cell::Cell, fmt, future::Future, ops::Deref, panic::Location, rc::Rc};
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// A `!Send` handle that aborts the task when dropped, for single-threaded runtimes and `LocalSet`.
+///
+/// A `LocalDropHandle` behaves like a `DropHandle`: the task will only be aborted when the last `LocalDropHandle` is dropped.
+/// It is backed by an `Rc` instead of an `Arc`, so cloning and dropping it does not involve any atomic operation,
+/// but it cannot be sent to another thread.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::LocalDropHandle;
+/// use std::{future::pending, rc::Rc};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     tokio::task::LocalSet::new()
+///         .run_until(async {
+///             let local_drop_handle = LocalDropHandle::spawn_local(async {
+///                 let not_send = Rc::new(());
+///                 pending::<()>().await;
+///                 drop(not_send);
+///             });
+///             let local_drop_handle_clone = local_drop_handle.clone();
+///             // The task will be aborted when both `local_drop_handle` and `local_drop_handle_clone` go out of scope.
+///         })
+///         .await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LocalDropHandle(Rc<LocalShared>);
+
+/// The state shared by every clone of a `LocalDropHandle`, which aborts the task when the last clone drops it.
+struct LocalShared {
+    abort_handle: AbortHandle,
+    armed: Cell<bool>,
+}
+
+impl LocalDropHandle {
+    #[track_caller]
+    fn new(abort_handle: AbortHandle) -> Self {
+        debug!(
+            task.id = %abort_handle.id(),
+            location = %Location::caller(),
+            "create LocalDropHandle"
+        );
+        Self(Rc::new(LocalShared {
+            abort_handle,
+            armed: Cell::new(true),
+        }))
+    }
+
+    /// Spawns a `!Send` task on the current `LocalSet`, and returns a `LocalDropHandle` that aborts it when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a `LocalSet`.
+    #[track_caller]
+    pub fn spawn_local<F>(future: F) -> Self
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        tokio::task::spawn_local(future).into()
+    }
+
+    /// Detaches the task, so it keeps running after the last `LocalDropHandle` is dropped.
+    ///
+    /// This disarms every clone of this `LocalDropHandle`, and returns the underlying `AbortHandle`.
+    #[must_use = "the task can no longer be aborted if the returned `AbortHandle` is dropped"]
+    pub fn detach(self) -> AbortHandle {
+        debug!(task.id = %self.0.abort_handle.id(), "detach LocalDropHandle");
+        self.0.armed.set(false);
+        self.0.abort_handle.clone()
+    }
+
+    /// Returns `true` if the task will be aborted when the last `LocalDropHandle` is dropped, i.e. it has not been detached.
+    #[must_use]
+    pub fn is_armed(&self) -> bool {
+        self.0.armed.get()
+    }
+}
+
+impl fmt::Debug for LocalDropHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalDropHandle")
+            .field("abort_handle", &self.0.abort_handle)
+            .field("holders", &Rc::strong_count(&self.0))
+            .field("armed", &self.0.armed.get())
+            .finish()
+    }
+}
+
+impl Deref for LocalDropHandle {
+    type Target = AbortHandle;
+
+    fn deref(&self) -> &AbortHandle {
+        &self.0.abort_handle
+    }
+}
+
+impl From<AbortHandle> for LocalDropHandle {
+    #[track_caller]
+    fn from(value: AbortHandle) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<JoinHandle<T>> for LocalDropHandle {
+    #[track_caller]
+    fn from(value: JoinHandle<T>) -> Self {
+        Self::new(value.abort_handle())
+    }
+}
+
+/// When the last `LocalDropHandle` is dropped, the task will be aborted, unless it has been detached.
+impl Drop for LocalShared {
+    fn drop(&mut self) {
+        if !self.armed.get() {
+            debug!(
+                task.id = %self.abort_handle.id(),
+                "drop LocalDropHandle: task is detached"
+            );
+            return;
+        }
+        debug!(
+            task.id = %self.abort_handle.id(),
+            "drop LocalDropHandle: abort task"
+        );
+        self.abort_handle.abort();
+    }
+}
@@ -0,0 +1,202 @@
+//! The logging backend of the crate, chosen with cargo features, and its runtime verbosity.
+//!
+//! The events are emitted through `tracing` with the `tracing` cargo feature (the default), or else through `log` with the `log` cargo feature.
+//! Without either of them, nothing is logged.
+//!
+//! The `error!`, `warn!`, `info!`, `debug!` and `trace!` macros of this module are available to the whole crate,
+//! as it is declared with `#[macro_use]` before the other modules.
+
+#[cfg(not(feature = "tracing"))]
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The verbosity of the events emitted by this crate, see `set_log_level()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum LogLevel {
+    /// No event is emitted.
+    Off,
+    /// Only the `error` events are emitted.
+    Error,
+    /// The `warn` events and above are emitted.
+    Warn,
+    /// The `info` events and above are emitted.
+    Info,
+    /// The `debug` events and above are emitted.
+    Debug,
+    /// Every event is emitted, including the `trace` event of every `DropHandle` drop.
+    Trace,
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Trace as u8);
+
+/// Sets the maximum level of the events emitted by this crate, `LogLevel::Trace` by default.
+///
+/// This filters the events before they reach the logging backend, which can filter them further.
+/// The events are emitted through `tracing` with the `tracing` cargo feature (the default),
+/// or else through `log` with the `log` cargo feature: without either of them, nothing is logged.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::LogLevel;
+///
+/// // Silence the `trace` event of every `DropHandle` drop, on a hot path
+/// drop_handle::set_log_level(LogLevel::Debug);
+/// assert_eq!(drop_handle::log_level(), LogLevel::Debug);
+/// ```
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns the maximum level of the events emitted by this crate.
+#[must_use]
+pub fn log_level() -> LogLevel {
+    match LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Off,
+        1 => LogLevel::Error,
+        2 => LogLevel::Warn,
+        3 => LogLevel::Info,
+        4 => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
+
+/// Returns `true` if the events of the given level are emitted.
+pub fn enabled(level: LogLevel) -> bool {
+    level as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Emits an event with the syntax of the `tracing` macros, through the logging backend.
+///
+/// With `log`, the fields are appended to the message as `name=value`, skipping the `Option` fields that are `None` like `tracing` does,
+/// and the `parent` span is ignored.
+macro_rules! event {
+    ($level:ident, $tracing:ident, $($arg:tt)+) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::$level) {
+            #[cfg(feature = "tracing")]
+            ::tracing::$tracing!($($arg)+);
+            #[cfg(not(feature = "tracing"))]
+            $crate::logging::log_event!($level, $($arg)+);
+        }
+    };
+}
+
+/// Turns the fields of a `tracing` event into a format string and its arguments.
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_event {
+    ($level:ident, parent: $parent:expr, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {} {} $($rest)+)
+    };
+    ($level:ident, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {} {} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} $($name:ident).+ = ?$value:expr, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* "{}"} {$($args,)* $crate::logging::LogField(stringify!($($name).+), (&$value).field_value())} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} $($name:ident).+ = %$value:expr, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* " ", stringify!($($name).+), "={}"} {$($args,)* $value} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} $($name:ident).+ = $value:expr, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* "{}"} {$($args,)* $crate::logging::LogField(stringify!($($name).+), (&$value).field_value())} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} ?$name:ident, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* "{}"} {$($args,)* $crate::logging::LogField(stringify!($name), (&$name).field_value())} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} %$name:ident, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* " ", stringify!($name), "={}"} {$($args,)* $name} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} $name:ident, $($rest:tt)+) => {
+        $crate::logging::log_event!(@fields $level {$($fmt,)* "{}"} {$($args,)* $crate::logging::LogField(stringify!($name), (&$name).field_value())} $($rest)+)
+    };
+    (@fields $level:ident {$($fmt:expr),*} {$($args:expr),*} $message:literal $(, $message_args:expr)* $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::logging::{FieldValue as _, OptionalFieldValue as _};
+        #[cfg(feature = "log")]
+        ::log::log!(::log::Level::$level, concat!($message $(, $fmt)*) $(, $message_args)* $(, $args)*);
+        // Without a logging backend, still use the arguments, so they are not reported as unused
+        #[cfg(not(feature = "log"))]
+        let _ = || {
+            let _ = format_args!(concat!($message $(, $fmt)*) $(, $message_args)* $(, $args)*);
+        };
+    }};
+}
+
+/// A field of a `log` event, written as ` name=value`, or not at all if its value is `None`.
+#[cfg(not(feature = "tracing"))]
+pub struct LogField<'a>(pub &'static str, pub Option<&'a dyn fmt::Debug>);
+
+#[cfg(not(feature = "tracing"))]
+impl fmt::Display for LogField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.1
+            .map_or(Ok(()), |value| write!(f, " {}={value:?}", self.0))
+    }
+}
+
+/// The value of an `Option` field, which is skipped if it is `None`.
+///
+/// `log_event!` calls `(&value).field_value()`, which resolves to this trait for an `Option`,
+/// and through auto-ref to `FieldValue` for any other value.
+#[cfg(not(feature = "tracing"))]
+pub trait OptionalFieldValue {
+    fn field_value(&self) -> Option<&dyn fmt::Debug>;
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<T: fmt::Debug> OptionalFieldValue for Option<T> {
+    fn field_value(&self) -> Option<&dyn fmt::Debug> {
+        self.as_ref().map(|value| value as &dyn fmt::Debug)
+    }
+}
+
+/// The value of any field that is not an `Option`, see `OptionalFieldValue`.
+#[cfg(not(feature = "tracing"))]
+pub trait FieldValue {
+    fn field_value(&self) -> Option<&dyn fmt::Debug>;
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<T: fmt::Debug> FieldValue for &T {
+    fn field_value(&self) -> Option<&dyn fmt::Debug> {
+        Some(*self)
+    }
+}
+
+#[allow(unused_macros)]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::logging::event!(Error, error, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::logging::event!(Warn, warn, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::logging::event!(Info, info, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::logging::event!(Debug, debug, $($arg)+)
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::logging::event!(Trace, trace, $($arg)+)
+    };
+}
+
+pub(crate) use event;
+#[cfg(not(feature = "tracing"))]
+pub(crate) use log_event;
@@ -0,0 +1,94 @@
+//! Model checks of the holder counter deciding which `DropHandle` aborts the task.
+//!
+//! The clone and drop tests go through the real `Clone` and `Drop` implementations of `DropHandle`.
+//! Only the `Holders` counter is modelled: the `Arc` of the shared state is a std `Arc`, whose own counter is not,
+//! but it never decides whether the task is aborted.
+//! `WeakDropHandle` holds a Tokio task, so the upgrade test exercises `Holders::try_acquire` directly.
+//!
+//! Run with `RUSTFLAGS="--cfg drop_handle_loom" cargo test --release --lib`.
+
+#[cfg(feature = "tokio")]
+use crate::shared::Holders;
+use crate::{Abortable, DropHandle};
+use loom::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+/// A task handle counting how many times it is aborted.
+#[derive(Debug)]
+struct AbortCounter(Arc<AtomicUsize>);
+
+impl Abortable for AbortCounter {
+    fn abort(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Creates a `DropHandle` holding an `AbortCounter`, and returns the number of aborts along with it.
+fn counted_drop_handle() -> (DropHandle<AbortCounter>, Arc<AtomicUsize>) {
+    let aborts = Arc::new(AtomicUsize::new(0));
+    let drop_handle = DropHandle::from_abortable(AbortCounter(aborts.clone()));
+    (drop_handle, aborts)
+}
+
+#[test]
+fn concurrent_drops_abort_exactly_once() {
+    loom::model(|| {
+        let (drop_handle, aborts) = counted_drop_handle();
+        let drop_handle_clone = drop_handle.clone();
+
+        let thread = thread::spawn(move || drop(drop_handle_clone));
+        drop(drop_handle);
+        thread.join().unwrap();
+
+        assert_eq!(aborts.load(Ordering::SeqCst), 1);
+    });
+}
+
+#[test]
+fn concurrent_clone_and_drops_abort_exactly_once() {
+    loom::model(|| {
+        let (drop_handle, aborts) = counted_drop_handle();
+        // Send a clone to another thread which clones it again before dropping both
+        let drop_handle_clone = drop_handle.clone();
+
+        let thread = thread::spawn(move || {
+            let drop_handle_clone_clone = drop_handle_clone.clone();
+            drop(drop_handle_clone);
+            drop(drop_handle_clone_clone);
+        });
+        drop(drop_handle);
+        thread.join().unwrap();
+
+        assert_eq!(aborts.load(Ordering::SeqCst), 1);
+    });
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn concurrent_upgrade_and_drop_abort_exactly_once() {
+    loom::model(|| {
+        let holders = Arc::new(Holders::new());
+
+        // Upgrade a weak handle while the last handle is dropped, like `WeakDropHandle::upgrade` does
+        let holders_clone = holders.clone();
+        let thread = thread::spawn(move || {
+            if holders_clone.try_acquire() {
+                usize::from(holders_clone.release() == 1)
+            } else {
+                0
+            }
+        });
+        let main_is_last = holders.release() == 1;
+        let thread_last_count = thread.join().unwrap();
+
+        assert_eq!(usize::from(main_is_last) + thread_last_count, 1);
+        assert_eq!(holders.count(), 0);
+        // A released task is never resurrected
+        assert!(!holders.try_acquire());
+    });
+}
@@ -0,0 +1,46 @@
+use std::{any::Any, fmt, sync::Arc};
+use tokio::task::Id;
+
+/// A callback receiving the payload of a task that panicked, along with the task id.
+pub type PanicSink = Arc<dyn Fn(Id, Box<dyn Any + Send>) + Send + Sync>;
+
+/// What to do when a task held by a `DropHandle` panics, see `DropHandle::set_panic_policy()`.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum PanicPolicy {
+    /// Drop the panic payload, as Tokio does when the `JoinHandle` of a task is dropped.
+    #[default]
+    Ignore,
+    /// Log the panic message with the task id, through `tracing`.
+    Log,
+    /// Forward the panic payload to a sink.
+    Sink(PanicSink),
+    /// Re-raise the panic in the first caller awaiting the task with `wait()`, `abort_and_wait()` or `shutdown()`.
+    Resume,
+}
+
+impl PanicPolicy {
+    /// Creates a policy forwarding the panic payloads to `sink`.
+    pub fn sink(sink: impl Fn(Id, Box<dyn Any + Send>) + Send + Sync + 'static) -> Self {
+        Self::Sink(Arc::new(sink))
+    }
+}
+
+impl fmt::Debug for PanicPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ignore => f.write_str("Ignore"),
+            Self::Log => f.write_str("Log"),
+            Self::Sink(_) => f.write_str("Sink(..)"),
+            Self::Resume => f.write_str("Resume"),
+        }
+    }
+}
+
+/// Returns the message of a panic payload, if it is a string.
+pub fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
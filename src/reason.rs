@@ -0,0 +1,41 @@
+use std::{borrow::Cow, fmt};
+
+/// Why a task was aborted through its `DropHandle`, as returned by `DropHandle::abort_reason()`.
+///
+/// Only the first reason is recorded: aborting a task that has already been aborted does not change it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AbortReason {
+    /// The last `DropHandle` holding the task was dropped.
+    LastHandleDropped,
+    /// The task was aborted with `DropHandle::abort()`.
+    Explicit,
+    /// The deadline of the task elapsed.
+    Deadline,
+    /// The lease of the task expired without being renewed.
+    LeaseExpired,
+    /// The parent task was aborted, see `DropHandle::attach_child()`.
+    ParentAborted,
+    /// The task was aborted with `DropHandle::abort_with()`, with a custom reason.
+    Custom(Cow<'static, str>),
+}
+
+impl AbortReason {
+    /// Creates a custom reason.
+    pub fn custom(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self::Custom(reason.into())
+    }
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LastHandleDropped => f.write_str("last handle dropped"),
+            Self::Explicit => f.write_str("explicit"),
+            Self::Deadline => f.write_str("deadline"),
+            Self::LeaseExpired => f.write_str("lease expired"),
+            Self::ParentAborted => f.write_str("parent aborted"),
+            Self::Custom(reason) => f.write_str(reason),
+        }
+    }
+}
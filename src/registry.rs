@@ -0,0 +1,154 @@
+//! A global registry of every live `DropHandle` holding a Tokio task, for introspection.
+//!
+//! This module is only available with the `registry` cargo feature.
+//!
+//! Example usage:
+//! ```
+//! use std::future::pending;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let drop_handle = drop_handle::spawn(pending::<()>());
+//!     for info in drop_handle::registry::snapshot() {
+//!         println!(
+//!             "task {:?} created at {} is held by {} handle(s)",
+//!             info.id, info.location, info.holders
+//!         );
+//!     }
+//! }
+//! ```
+
+use crate::{Abortable, shared::Shared};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    panic::Location,
+    sync::{
+        Arc, Mutex, PoisonError, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+use tokio::task::Id;
+
+/// Every live `DropHandle`, keyed by registration order.
+static REGISTRY: Mutex<BTreeMap<u64, Entry>> = Mutex::new(BTreeMap::new());
+static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+
+/// Information about a live `DropHandle`, as returned by `snapshot()`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct HandleInfo {
+    /// The task id.
+    pub id: Id,
+    /// The name of the task, if it is named.
+    pub name: Option<Cow<'static, str>>,
+    /// Where the `DropHandle` was created.
+    pub location: &'static Location<'static>,
+    /// When the `DropHandle` was created.
+    pub created_at: SystemTime,
+    /// The number of `DropHandle` clones holding the task.
+    pub holders: usize,
+    /// Whether the task will be aborted when the last `DropHandle` is dropped, i.e. it has not been detached.
+    pub armed: bool,
+    /// Whether the task has finished.
+    pub finished: bool,
+}
+
+/// Returns information about every live `DropHandle` holding a Tokio task, in creation order.
+///
+/// Clones of a `DropHandle` are reported once, with their number in `holders`.
+#[must_use]
+pub fn snapshot() -> Vec<HandleInfo> {
+    // Upgrade the handles under the lock, but read them outside of it:
+    // dropping the upgraded `Arc` may drop the last reference to a `Shared`, which unregisters itself.
+    #[allow(clippy::needless_collect)]
+    let entries: Vec<_> = REGISTRY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .values()
+        .filter_map(|entry| Some((entry.shared.upgrade()?, entry.location, entry.created_at)))
+        .collect();
+    entries
+        .into_iter()
+        .filter_map(|(shared, location, created_at)| shared.info(location, created_at))
+        .collect()
+}
+
+struct Entry {
+    shared: Weak<dyn Registered>,
+    location: &'static Location<'static>,
+    created_at: SystemTime,
+}
+
+/// The shared state of a registered `DropHandle`, whatever its task handle.
+trait Registered: Send + Sync {
+    /// Returns information about the `DropHandle`, or `None` if it is no longer held.
+    fn info(
+        &self,
+        location: &'static Location<'static>,
+        created_at: SystemTime,
+    ) -> Option<HandleInfo>;
+}
+
+impl<A: Abortable> Registered for Shared<A> {
+    fn info(
+        &self,
+        location: &'static Location<'static>,
+        created_at: SystemTime,
+    ) -> Option<HandleInfo> {
+        let holders = self.holders.count();
+        if holders == 0 {
+            return None;
+        }
+        Some(HandleInfo {
+            id: self.id?,
+            name: self.name.get().cloned(),
+            location,
+            created_at,
+            holders,
+            armed: self.is_armed(),
+            finished: self.is_finished(),
+        })
+    }
+}
+
+/// The registration of a `DropHandle`, removed from the registry when dropped along with the shared state.
+#[derive(Debug)]
+pub(crate) struct Registration(u64);
+
+impl Registration {
+    fn new(shared: Weak<dyn Registered>, location: &'static Location<'static>) -> Self {
+        let key = NEXT_KEY.fetch_add(1, Ordering::Relaxed);
+        let entry = Entry {
+            shared,
+            location,
+            created_at: SystemTime::now(),
+        };
+        REGISTRY
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, entry);
+        Self(key)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        REGISTRY
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.0);
+    }
+}
+
+/// Registers a `DropHandle` when its shared state is created.
+#[track_caller]
+pub(crate) fn register<A: Abortable>(mut shared: Shared<A>) -> Arc<Shared<A>> {
+    let location = Location::caller();
+    Arc::new_cyclic(|weak: &Weak<Shared<A>>| {
+        let weak: Weak<dyn Registered> = weak.clone();
+        shared.registration = Some(Registration::new(weak, location));
+        shared
+    })
+}
@@ -0,0 +1,151 @@
+use crate::DropHandleGroup;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+};
+use tokio::task::JoinHandle;
+use tokio_util::task::TaskTracker;
+
+/// What to do with the tasks spawned in a scope that are still running when the scope completes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ScopeExit {
+    /// Abort them.
+    #[default]
+    Abort,
+    /// Wait for them to finish.
+    Wait,
+}
+
+/// Runs a scope whose spawned tasks cannot outlive it.
+///
+/// Every task spawned with `Scope::spawn` is aborted when the scope completes, or when the returned future is dropped.
+///
+/// Example usage:
+/// ```
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let output = drop_handle::scope(|scope| async move {
+///         scope.spawn(async {
+///             loop {
+///                 println!("Task is running...");
+///                 sleep(Duration::from_secs(1)).await;
+///             }
+///         });
+///         scope.spawn(async { 42 }).await.unwrap()
+///     })
+///     .await;
+///     assert_eq!(output, 42);
+///     // The first task has been aborted when the scope completed.
+/// }
+/// ```
+pub async fn scope<F, Fut>(f: F) -> Fut::Output
+where
+    F: FnOnce(Scope) -> Fut,
+    Fut: Future,
+{
+    scope_with(ScopeExit::Abort, f).await
+}
+
+/// Runs a scope whose spawned tasks cannot outlive it, choosing what to do with the tasks still running when it completes.
+///
+/// With `ScopeExit::Wait`, the returned future only resolves once every spawned task has finished.
+/// In any case, the tasks still running are aborted if the returned future is dropped.
+pub async fn scope_with<F, Fut>(exit: ScopeExit, f: F) -> Fut::Output
+where
+    F: FnOnce(Scope) -> Fut,
+    Fut: Future,
+{
+    let scope = Scope::new();
+    let _guard = ScopeGuard(scope.clone());
+    let output = f(scope.clone()).await;
+    if exit == ScopeExit::Wait {
+        scope.0.tracker.close();
+        scope.0.tracker.wait().await;
+    }
+    output
+}
+
+/// A handle to spawn tasks that cannot outlive their scope, created by `scope()`.
+///
+/// A `Scope` can be cloned and moved into the tasks it spawned, to spawn more tasks in the same scope.
+#[derive(Clone, Debug)]
+pub struct Scope(Arc<ScopeState>);
+
+#[derive(Debug)]
+struct ScopeState {
+    /// The tasks of the scope, `None` once the scope has ended.
+    group: Mutex<Option<DropHandleGroup>>,
+    tracker: TaskTracker,
+}
+
+impl Scope {
+    fn new() -> Self {
+        Self(Arc::new(ScopeState {
+            group: Mutex::new(Some(DropHandleGroup::new())),
+            tracker: TaskTracker::new(),
+        }))
+    }
+
+    /// Spawns a task on the current Tokio runtime, owned by the scope.
+    ///
+    /// The returned `JoinHandle` can be used to await the task's output, but dropping it does not detach the task from the scope.
+    /// If the scope has already ended, the task is aborted right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    #[track_caller]
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let join_handle = self.0.tracker.spawn(future);
+        if let Some(group) = &mut *self.0.group.lock().unwrap_or_else(PoisonError::into_inner) {
+            group.insert(join_handle.abort_handle());
+        } else {
+            debug!(task.id = %join_handle.id(), "scope has ended: abort task");
+            join_handle.abort();
+        }
+        join_handle
+    }
+
+    /// Returns the number of tasks of the scope that are still running.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0
+            .group
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map_or(0, DropHandleGroup::len)
+    }
+
+    /// Returns `true` if no task of the scope is still running.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Ends the scope when dropped, aborting its tasks that are still running.
+struct ScopeGuard(Scope);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let group = self
+            .0
+            .0
+            .group
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        debug!(
+            tasks = group.as_ref().map_or(0, DropHandleGroup::len),
+            "end of scope: abort tasks"
+        );
+        drop(group);
+    }
+}
@@ -0,0 +1,292 @@
+#[cfg(feature = "registry")]
+use crate::registry::Registration;
+use crate::{AbortReason, Abortable, DropHandle};
+#[cfg(feature = "tokio")]
+use crate::{deadline::Deadline, graceful::GracefulShutdown, hooks::TaskHooks};
+#[cfg(drop_handle_loom)]
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+#[cfg(not(drop_handle_loom))]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{
+    borrow::Cow,
+    fmt, mem,
+    sync::{Mutex, OnceLock, PoisonError, Weak},
+};
+#[cfg(feature = "tokio")]
+use tokio::{sync::watch, task::Id};
+#[cfg(feature = "tracing")]
+use tracing::Span;
+
+pub type OnAbort = Box<dyn FnOnce() + Send>;
+
+/// The state shared by every clone of a `DropHandle`.
+pub struct Shared<A: Abortable> {
+    pub abort_handle: A,
+    pub holders: Holders,
+    #[cfg(feature = "tokio")]
+    pub graceful: Option<GracefulShutdown>,
+    #[cfg(feature = "tokio")]
+    pub task_hooks: Option<Arc<TaskHooks>>,
+    /// The deadline or the lease of the task, watched by a task started when it is first set.
+    #[cfg(feature = "tokio")]
+    pub deadline: OnceLock<watch::Sender<Option<Deadline>>>,
+    /// The id of a Tokio task, stored so a `DropHandle` can be borrowed as its id, `None` for other task handles.
+    #[cfg(feature = "tokio")]
+    pub id: Option<Id>,
+    armed: AtomicBool,
+    reason: OnceLock<AbortReason>,
+    /// The human-readable name of the task, emitted in every event about it.
+    pub name: OnceLock<Cow<'static, str>>,
+    /// The span of a task spawned with `spawn_instrumented`, in which its lifecycle events are emitted.
+    #[cfg(feature = "tracing")]
+    pub span: Span,
+    on_abort: Mutex<Vec<OnAbort>>,
+    pub children: Mutex<Vec<DropHandle<A>>>,
+    pub parent: Mutex<Weak<Self>>,
+    #[cfg(feature = "registry")]
+    pub registration: Option<Registration>,
+}
+
+impl<A: Abortable> Shared<A> {
+    pub fn new(abort_handle: A) -> Self {
+        #[cfg(feature = "tokio")]
+        let id = abort_handle.tokio_id();
+        Self {
+            abort_handle,
+            holders: Holders::new(),
+            #[cfg(feature = "tokio")]
+            graceful: None,
+            #[cfg(feature = "tokio")]
+            task_hooks: None,
+            #[cfg(feature = "tokio")]
+            deadline: OnceLock::new(),
+            #[cfg(feature = "tokio")]
+            id,
+            armed: AtomicBool::new(true),
+            reason: OnceLock::new(),
+            name: OnceLock::new(),
+            #[cfg(feature = "tracing")]
+            span: Span::none(),
+            on_abort: Mutex::new(Vec::new()),
+            children: Mutex::new(Vec::new()),
+            parent: Mutex::new(Weak::new()),
+            #[cfg(feature = "registry")]
+            registration: None,
+        }
+    }
+
+    /// Returns `true` if the task will be aborted when the last `DropHandle` is dropped.
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Acquire)
+    }
+
+    /// Prevents the task from being aborted when the last `DropHandle` is dropped.
+    pub fn disarm(&self) {
+        self.armed.store(false, Ordering::Release);
+    }
+
+    /// Aborts the task, and recursively every child task.
+    pub fn abort(&self, reason: AbortReason) {
+        self.record_abort_reason(reason);
+        self.abort_handle.abort();
+        self.abort_children();
+    }
+
+    /// Returns `true` if the task has finished, as far as its task handle or its hooks can tell.
+    pub fn is_finished(&self) -> bool {
+        #[cfg(feature = "tokio")]
+        if let Some(task_hooks) = &self.task_hooks {
+            if task_hooks.outcome().is_some() {
+                return true;
+            }
+        }
+        self.abort_handle.is_finished()
+    }
+
+    /// Returns the id of the task, as emitted in the `task.id` field of the events about it.
+    pub const fn task_id(&self) -> TaskId<'_, A> {
+        TaskId(self)
+    }
+
+    /// Records why the task is aborted, unless a reason has already been recorded or the task has already finished.
+    pub fn record_abort_reason(&self, reason: AbortReason) {
+        if self.is_finished() {
+            return;
+        }
+        let recorded = self.reason.set(reason).is_ok();
+        #[cfg(feature = "tracing")]
+        if recorded && !self.span.is_none() {
+            if let Some(reason) = self.reason.get() {
+                info!(parent: &self.span, %reason, "aborted");
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = recorded;
+    }
+
+    /// Names the task, returning `false` if it is already named.
+    pub fn set_name(&self, name: Cow<'static, str>) -> bool {
+        self.name.set(name).is_ok()
+    }
+
+    /// Returns the name of the task, if it is named.
+    pub fn name(&self) -> Option<&str> {
+        self.name.get().map(|name| &**name)
+    }
+
+    /// Returns why the task was aborted, if it was.
+    pub fn abort_reason(&self) -> Option<AbortReason> {
+        self.reason.get().cloned()
+    }
+
+    /// Recursively aborts every child task, which are no longer attached to this task.
+    pub fn abort_children(&self) {
+        let children =
+            mem::take(&mut *self.children.lock().unwrap_or_else(PoisonError::into_inner));
+        for child in children {
+            *child
+                .0
+                .parent
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Weak::new();
+            debug!(
+                task.id = %child.0.task_id(),
+                name = child.0.name(),
+                parent.id = %self.task_id(),
+                reason = %AbortReason::ParentAborted,
+                "abort child task"
+            );
+            child.0.abort(AbortReason::ParentAborted);
+        }
+    }
+
+    /// Detaches every child task, so they keep running along with this detached task.
+    pub fn detach_children(&self) {
+        let children =
+            mem::take(&mut *self.children.lock().unwrap_or_else(PoisonError::into_inner));
+        for child in children {
+            *child
+                .0
+                .parent
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Weak::new();
+            debug!(
+                task.id = %child.0.task_id(),
+                name = child.0.name(),
+                parent.id = %self.task_id(),
+                parent.name = self.name(),
+                "detach child task"
+            );
+            child.0.disarm();
+        }
+    }
+
+    /// Registers a callback to run when the last `DropHandle` aborts the task.
+    pub fn on_abort(&self, callback: OnAbort) {
+        self.on_abort
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(callback);
+    }
+
+    /// Runs the `on_abort` callbacks.
+    pub fn run_on_abort(&self) {
+        for callback in self.take_on_abort() {
+            callback();
+        }
+    }
+
+    /// Takes the `on_abort` callbacks, to run them later.
+    pub fn take_on_abort(&self) -> Vec<OnAbort> {
+        mem::take(&mut *self.on_abort.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Names the task.
+    #[cfg(feature = "tokio")]
+    pub fn with_name(self, name: Cow<'static, str>) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    /// Shuts the task down gracefully instead of aborting it right away.
+    #[cfg(feature = "tokio")]
+    pub fn with_graceful(mut self, graceful: GracefulShutdown) -> Self {
+        self.graceful = Some(graceful);
+        self
+    }
+
+    /// Emits the lifecycle events of the task in the given span.
+    #[cfg(all(feature = "tokio", feature = "tracing"))]
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Reports the termination of a task spawned through this crate to its `on_finish` callbacks.
+    #[cfg(feature = "tokio")]
+    pub fn with_task_hooks(mut self, task_hooks: Arc<TaskHooks>) -> Self {
+        self.task_hooks = Some(task_hooks);
+        self
+    }
+}
+
+/// The id of a task, displayed as the Tokio task id, or else as the `Debug` representation of its task handle.
+pub struct TaskId<'a, A: Abortable>(&'a Shared<A>);
+
+impl<A: Abortable> fmt::Display for TaskId<'_, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "tokio")]
+        if let Some(id) = &self.0.id {
+            return fmt::Display::fmt(id, f);
+        }
+        fmt::Debug::fmt(&self.0.abort_handle, f)
+    }
+}
+
+/// The number of `DropHandle` currently holding a task.
+///
+/// Unlike `Arc::strong_count`, the decrement and the "was it the last one?" check are a single atomic operation,
+/// so exactly one holder observes that it was the last one, even when clones are dropped concurrently.
+#[derive(Debug)]
+pub struct Holders(AtomicUsize);
+
+impl Holders {
+    /// Creates a counter with a single holder.
+    #[allow(clippy::missing_const_for_fn)] // `loom` atomics cannot be created in a const context
+    pub fn new() -> Self {
+        Self(AtomicUsize::new(1))
+    }
+
+    /// Registers a new holder.
+    ///
+    /// The caller must already be a holder, so the counter can never go from 0 back to 1.
+    pub fn acquire(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Registers a new holder if there is still at least one, returning `false` if the last one was already released.
+    ///
+    /// Used by callers that are not holders themselves, so a released task is never resurrected.
+    #[cfg(feature = "tokio")]
+    pub fn try_acquire(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| {
+                (count != 0).then(|| count + 1)
+            })
+            .is_ok()
+    }
+
+    /// Unregisters a holder, returning the number of holders before the release.
+    ///
+    /// The holder that gets `1` was the last one.
+    pub fn release(&self) -> usize {
+        self.0.fetch_sub(1, Ordering::AcqRel)
+    }
+
+    /// Returns the current number of holders.
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Acquire)
+    }
+}
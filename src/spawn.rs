@@ -0,0 +1,232 @@
+use crate::{DropHandle, hooks::TaskHooks, shared::Shared};
+#[cfg(feature = "tracing")]
+use std::panic::Location;
+use std::{borrow::Cow, future::Future, sync::Arc, time::Duration};
+use tokio::{runtime::Handle, task::JoinHandle, time::Instant};
+#[cfg(feature = "tracing")]
+use tracing::{Instrument, Span, field, info_span};
+
+/// Spawns a task on the current Tokio runtime, and returns a `DropHandle` that aborts it when dropped.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// Example usage:
+/// ```
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle = drop_handle::spawn(async {
+///         loop {
+///             println!("Task is running...");
+///             sleep(Duration::from_secs(1)).await;
+///         }
+///     });
+///     // The task will be automatically aborted when `drop_handle` goes out of scope.
+/// }
+/// ```
+#[track_caller]
+pub fn spawn<F>(future: F) -> DropHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = tokio::spawn(task_hooks.wrap(future)).abort_handle();
+    DropHandle::new(Shared::new(abort_handle).with_task_hooks(task_hooks))
+}
+
+/// Spawns a named task on the current Tokio runtime, and returns a `DropHandle` that aborts it when dropped.
+///
+/// The name is emitted in every event about the task, see `DropHandle::with_name()`.
+/// When built with `--cfg tokio_unstable` and the `tracing` cargo feature, it also names the Tokio task, e.g. in `tokio-console`.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// Example usage:
+/// ```
+/// use std::future::pending;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle = drop_handle::spawn_named("worker", pending::<()>());
+///     assert_eq!(drop_handle.name(), Some("worker"));
+///     // The `drop DropHandle` event will mention the `worker` name when `drop_handle` goes out of scope.
+/// }
+/// ```
+#[track_caller]
+pub fn spawn_named<F>(name: impl Into<Cow<'static, str>>, future: F) -> DropHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let name = name.into();
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = spawn_task(&name, task_hooks.wrap(future)).abort_handle();
+    DropHandle::new(
+        Shared::new(abort_handle)
+            .with_name(name)
+            .with_task_hooks(task_hooks),
+    )
+}
+
+/// Spawns a task on the current Tokio runtime, naming the Tokio task when `tokio_unstable` and `tracing` are enabled.
+#[track_caller]
+fn spawn_task<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tracing"))]
+    return tokio::task::Builder::new()
+        .name(name)
+        .spawn(future)
+        .expect("failed to spawn task");
+    #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Spawns a task on the current Tokio runtime inside a `tracing` span, and returns a `DropHandle` that aborts it when dropped.
+///
+/// The task runs in a `task` span, child of the current span, with the `task.id` and `task.name` fields.
+/// The name is also the name of the `DropHandle`, see `spawn_named()`.
+/// The lifecycle of the task is reported by `created`, `aborted` and `finished` events in this span,
+/// with the `location`, `reason` and `outcome` fields respectively.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// This is only available with the `tracing` cargo feature.
+///
+/// Example usage:
+/// ```
+/// use std::future::pending;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle = drop_handle::spawn_instrumented("worker", async {
+///         tracing::info!("this event is emitted in the span of the task");
+///         pending::<()>().await;
+///     });
+///     // The `aborted` event will be emitted when `drop_handle` goes out of scope.
+/// }
+/// ```
+#[cfg(feature = "tracing")]
+#[track_caller]
+pub fn spawn_instrumented<F>(name: impl Into<Cow<'static, str>>, future: F) -> DropHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let name = name.into();
+    let span = info_span!(
+        parent: Span::current(),
+        "task",
+        task.id = field::Empty,
+        task.name = %name
+    );
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle =
+        spawn_task(&name, task_hooks.wrap(future).instrument(span.clone())).abort_handle();
+    span.record("task.id", field::display(abort_handle.id()));
+    info!(parent: &span, location = %Location::caller(), "created");
+    let finished_span = span.clone();
+    task_hooks.on_finish(Box::new(move |outcome| {
+        info!(parent: &finished_span, ?outcome, "finished");
+    }));
+    DropHandle::new(
+        Shared::new(abort_handle)
+            .with_name(name)
+            .with_span(span)
+            .with_task_hooks(task_hooks),
+    )
+}
+
+/// Spawns a task on the current Tokio runtime, and returns a `DropHandle` that aborts it when dropped, or once `timeout` has elapsed.
+///
+/// The timeout is a deadline shared by every clone of the `DropHandle`, see `DropHandle::set_deadline()`.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::AbortReason;
+/// use std::future::pending;
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle = drop_handle::spawn_with_timeout(Duration::from_millis(10), pending::<()>());
+///     sleep(Duration::from_millis(100)).await;
+///     assert_eq!(drop_handle.abort_reason(), Some(AbortReason::Deadline));
+/// }
+/// ```
+#[track_caller]
+pub fn spawn_with_timeout<F>(timeout: Duration, future: F) -> DropHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn(future).with_deadline(Instant::now() + timeout)
+}
+
+/// Spawns a task on the given Tokio runtime, and returns a `DropHandle` that aborts it when dropped.
+///
+/// Unlike `spawn`, this can be called from outside of a Tokio runtime.
+#[track_caller]
+pub fn spawn_on<F>(handle: &Handle, future: F) -> DropHandle
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = handle.spawn(task_hooks.wrap(future)).abort_handle();
+    DropHandle::new(Shared::new(abort_handle).with_task_hooks(task_hooks))
+}
+
+/// Spawns a `!Send` task on the current `LocalSet`, and returns a `DropHandle` that aborts it when dropped.
+///
+/// See `LocalDropHandle::spawn_local` for a handle that is not `Send` either, but avoids atomic operations.
+///
+/// # Panics
+///
+/// Panics if called from outside of a `LocalSet`.
+#[track_caller]
+pub fn spawn_local<F>(future: F) -> DropHandle
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = tokio::task::spawn_local(task_hooks.wrap(future)).abort_handle();
+    DropHandle::new(Shared::new(abort_handle).with_task_hooks(task_hooks))
+}
+
+/// Runs a blocking function on the blocking thread pool, and returns a `DropHandle` that aborts it when dropped.
+///
+/// Note that a blocking task can only be aborted before it starts running:
+/// once started, it will run to completion even if the last `DropHandle` is dropped.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+#[track_caller]
+pub fn spawn_blocking<F, R>(f: F) -> DropHandle
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let task_hooks = Arc::new(TaskHooks::default());
+    let abort_handle = tokio::task::spawn_blocking(task_hooks.wrap_blocking(f)).abort_handle();
+    DropHandle::new(Shared::new(abort_handle).with_task_hooks(task_hooks))
+}
@@ -0,0 +1,226 @@
+use crate::{DropHandle, DropJoinHandle, panic::panic_message};
+use std::{
+    fmt,
+    future::Future,
+    ops::Deref,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{task::AbortHandle, time::Instant};
+
+/// How a supervised task is restarted, see `supervise_with()`.
+///
+/// The delay before each restart starts at `initial_backoff`, and doubles at each restart up to `max_backoff`.
+/// It is reset to `initial_backoff` once an incarnation of the task has run for longer than the current delay,
+/// so a task that ran healthily for a while is restarted quickly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RestartPolicy {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: Option<usize>,
+}
+
+impl RestartPolicy {
+    /// Sets the delay before the first restart, 100 milliseconds by default.
+    #[must_use]
+    pub const fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum delay between two restarts, 30 seconds by default.
+    #[must_use]
+    pub const fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the number of restarts after which the task is given up, unlimited by default.
+    #[must_use]
+    pub const fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = Some(max_restarts);
+        self
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            max_restarts: None,
+        }
+    }
+}
+
+/// Why a supervised task terminated, as returned by `Supervisor::last_error()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SupervisorError {
+    /// The task returned, although it is expected to run until its supervisor is dropped.
+    Exited,
+    /// The task panicked, with the panic message.
+    Panicked(String),
+    /// The task was aborted, or its runtime was shut down.
+    Cancelled,
+}
+
+impl fmt::Display for SupervisorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exited => f.write_str("task exited"),
+            Self::Panicked(message) => write!(f, "task panicked: {message}"),
+            Self::Cancelled => f.write_str("task cancelled"),
+        }
+    }
+}
+
+/// Keeps a task running until dropped, restarting it with the default `RestartPolicy` whenever it panics or returns.
+///
+/// `factory` is called to create each incarnation of the task, which is spawned on the current Tokio runtime.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+///
+/// Example usage:
+/// ```
+/// use tokio::time::{sleep, Duration};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let supervisor = drop_handle::supervise(|| async {
+///         loop {
+///             println!("Worker is running...");
+///             sleep(Duration::from_secs(1)).await;
+///         }
+///     });
+///     // The worker is restarted if it panics, until `supervisor` goes out of scope.
+///     assert_eq!(supervisor.restarts(), 0);
+/// }
+/// ```
+#[track_caller]
+pub fn supervise<F, Fut>(factory: F) -> Supervisor
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    supervise_with(RestartPolicy::default(), factory)
+}
+
+/// Keeps a task running until dropped, restarting it according to `policy` whenever it panics or returns.
+///
+/// Once the `max_restarts` budget of the policy is exhausted, the task is given up and no longer restarted.
+///
+/// # Panics
+///
+/// Panics if called from outside of a Tokio runtime.
+#[track_caller]
+pub fn supervise_with<F, Fut>(policy: RestartPolicy, mut factory: F) -> Supervisor
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let state = Arc::new(SupervisorState::default());
+    let supervisor_state = state.clone();
+    let drop_handle = crate::spawn(async move {
+        let mut backoff = policy.initial_backoff;
+        loop {
+            // Dropping the supervisor aborts this loop, which aborts the current incarnation along with it
+            let started_at = Instant::now();
+            let incarnation: DropJoinHandle<_> = tokio::spawn(factory()).into();
+            let id = incarnation.id();
+            let error = match incarnation.await {
+                Ok(_) => SupervisorError::Exited,
+                Err(error) if error.is_panic() => {
+                    let payload = error.into_panic();
+                    SupervisorError::Panicked(
+                        panic_message(&*payload)
+                            .unwrap_or("Box<dyn Any>")
+                            .to_owned(),
+                    )
+                }
+                Err(_) => SupervisorError::Cancelled,
+            };
+            let restarts = supervisor_state.restarts.load(Ordering::Relaxed);
+            warn!(task.id = %id, %error, restarts, "supervised task terminated");
+            *supervisor_state
+                .last_error
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(error);
+            if policy.max_restarts.is_some_and(|max| restarts >= max) {
+                warn!(task.id = %id, restarts, "supervised task restart budget exhausted: give up");
+                return;
+            }
+            if started_at.elapsed() > backoff {
+                backoff = policy.initial_backoff;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(policy.max_backoff);
+            supervisor_state.restarts.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    Supervisor { drop_handle, state }
+}
+
+/// A handle that keeps a task running until dropped, created by `supervise()`.
+///
+/// Dropping the `Supervisor` aborts both the restart loop and the current incarnation of the task.
+/// Awaiting the restart loop is not possible, but `is_finished()` returns `true` once the task has been given up.
+#[derive(Debug)]
+pub struct Supervisor {
+    drop_handle: DropHandle,
+    state: Arc<SupervisorState>,
+}
+
+#[derive(Debug, Default)]
+struct SupervisorState {
+    restarts: AtomicUsize,
+    last_error: Mutex<Option<SupervisorError>>,
+}
+
+impl Supervisor {
+    /// Aborts the restart loop along with the current incarnation of the task, see `DropHandle::abort()`.
+    ///
+    /// This shadows the `abort()` method of the underlying `AbortHandle`, which only aborts the restart loop itself.
+    pub fn abort(&self) {
+        self.drop_handle.abort();
+    }
+
+    /// Returns the number of times the task has been restarted.
+    #[must_use]
+    pub fn restarts(&self) -> usize {
+        self.state.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Returns why the last incarnation of the task terminated, if any did.
+    #[must_use]
+    pub fn last_error(&self) -> Option<SupervisorError> {
+        self.state
+            .last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns a `DropHandle` sharing the ownership of the restart loop.
+    ///
+    /// The task will keep being restarted as long as this `Supervisor` or any of the returned `DropHandle` is alive.
+    #[must_use]
+    pub fn drop_handle(&self) -> DropHandle {
+        self.drop_handle.clone()
+    }
+}
+
+impl Deref for Supervisor {
+    type Target = AbortHandle;
+
+    fn deref(&self) -> &AbortHandle {
+        &self.drop_handle
+    }
+}
@@ -1,7 +1,59 @@
-use crate::DropHandle;
-use std::{sync::Arc, time::Duration};
+use crate::{
+    AbortOnDropExt, AbortReason, DropHandle, DropHandleGroup, DropJoinHandle, LocalDropHandle,
+    PanicPolicy, RestartPolicy, ScopeExit, SupervisorError, TaskOutcome, UniqueDropHandle,
+    spawn_graceful,
+};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::task::{AbortHandle, JoinHandle};
 use tracing::Level;
 
+/// Returns a future that never completes and holds a clone of `counter` until it is dropped, e.g. when its task is aborted.
+fn hold_until_aborted(counter: &Arc<String>) -> impl Future<Output = ()> + use<> {
+    let counter = counter.clone();
+    async move {
+        std::future::pending::<()>().await;
+        drop(counter);
+    }
+}
+
+/// Spawns a task that never finishes and holds a clone of `counter` until it is aborted.
+fn spawn_pending(counter: &Arc<String>) -> JoinHandle<()> {
+    tokio::spawn(hold_until_aborted(counter))
+}
+
+/// Serialises the tests changing the log level with the tests checking the emitted events.
+static LOG_LEVEL: tokio::sync::RwLock<()> = tokio::sync::RwLock::const_new(());
+
+/// A log buffer, written by a `tracing` subscriber.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+#[cfg(feature = "tracing")]
+impl Logs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().expect("poisoned logs")).into_owned()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl std::io::Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("poisoned logs").extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[tokio::test]
 async fn test_drop_handle() {
     let subscriber = tracing_subscriber::fmt()
@@ -44,3 +96,1112 @@ async fn test_drop_handle() {
     // ... so there should be only 1 counter left (this one). This attests that the task was successfully terminated.
     assert_eq!(Arc::strong_count(&arc_counter), 1);
 }
+
+#[tokio::test]
+async fn test_drop_join_handle() {
+    // Awaiting the handle returns the task's output
+    let drop_join_handle: DropJoinHandle<u32> = tokio::spawn(async { 42 }).into();
+    assert_eq!(drop_join_handle.await.unwrap(), 42);
+
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_join_handle: DropJoinHandle<()> = spawn_pending(&arc_counter).into();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+    // Keep only the abort side: the task must stay alive as long as the `DropHandle` is
+    let drop_handle: DropHandle = drop_join_handle.into();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!drop_handle.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_drops() {
+    let arc_counter = Arc::new(String::from("counter"));
+    for _ in 0..100 {
+        let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let drop_handle = drop_handle.clone();
+                std::thread::spawn(move || drop(drop_handle))
+            })
+            .collect();
+        drop(drop_handle);
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    // Every task must have been aborted by exactly one of its handles
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test]
+async fn test_spawn_graceful() {
+    // A task that honors the cancellation token finishes on its own
+    let shut_down = Arc::new(AtomicBool::new(false));
+    let shut_down_clone = shut_down.clone();
+    let drop_handle = spawn_graceful(Duration::from_secs(60), |token| async move {
+        token.cancelled().await;
+        shut_down_clone.store(true, Ordering::SeqCst);
+    });
+    let abort_handle = AbortHandle::clone(&drop_handle);
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(shut_down.load(Ordering::SeqCst));
+    assert!(abort_handle.is_finished());
+
+    // A task that ignores the cancellation token is aborted once the grace period has elapsed
+    let arc_counter = Arc::new(String::from("counter"));
+    let arc_counter_clone = arc_counter.clone();
+    let drop_handle = spawn_graceful(Duration::from_millis(200), |_token| async move {
+        std::future::pending::<()>().await;
+        drop(arc_counter_clone);
+    });
+    let aborted = Arc::new(AtomicUsize::new(0));
+    let aborted_clone = aborted.clone();
+    drop_handle.on_abort(move || {
+        aborted_clone.fetch_add(1, Ordering::SeqCst);
+    });
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+    assert_eq!(aborted.load(Ordering::SeqCst), 0);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+
+    // The `on_abort` callbacks are not run for a task that completes within the grace period
+    let drop_handle = spawn_graceful(Duration::from_millis(200), |token| async move {
+        token.cancelled().await;
+    });
+    let aborted_clone = aborted.clone();
+    drop_handle.on_abort(move || {
+        aborted_clone.fetch_add(1, Ordering::SeqCst);
+    });
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_drop_handle_group() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let mut group = DropHandleGroup::new();
+    group.insert(spawn_pending(&arc_counter));
+    group.insert(spawn_pending(&arc_counter).abort_handle());
+    group.spawn(async {});
+    // The finished task that cannot be joined is removed from the group
+    group.insert(tokio::spawn(async {}).abort_handle());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    // The finished task that can be joined is counted until it is joined
+    assert_eq!(group.len(), 3);
+
+    // Only the finished task can be joined, the others are still running
+    assert!(group.join_next().await.unwrap().is_ok());
+    assert_eq!(group.len(), 2);
+    assert_eq!(Arc::strong_count(&arc_counter), 3);
+
+    drop(group);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // Aborted tasks are returned as cancelled by `join_next()`
+    let mut group: DropHandleGroup = (0..2).map(|_| spawn_pending(&arc_counter)).collect();
+    group.abort_all();
+    assert!(group.join_next().await.unwrap().unwrap_err().is_cancelled());
+    assert!(group.join_next().await.unwrap().unwrap_err().is_cancelled());
+    assert!(group.join_next().await.is_none());
+    assert!(group.is_empty());
+
+    // A finished `JoinHandle` is released on the next insert, but its output can still be joined
+    let mut group = DropHandleGroup::new();
+    group.insert(tokio::spawn(async { 42 }));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    group.insert(tokio::spawn(std::future::pending()));
+    assert_eq!(group.len(), 2);
+    assert_eq!(group.join_next().await.unwrap().unwrap(), 42);
+    assert_eq!(group.len(), 1);
+}
+
+#[tokio::test]
+async fn test_weak_drop_handle() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    let weak_drop_handle = drop_handle.downgrade();
+    assert_eq!(weak_drop_handle.id(), drop_handle.id());
+    assert!(weak_drop_handle.is_alive());
+
+    // Upgrading keeps the task alive after the original handle is dropped
+    let upgraded = weak_drop_handle.upgrade().unwrap();
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(weak_drop_handle.is_alive());
+    assert!(!weak_drop_handle.is_finished());
+
+    // The weak handle does not keep the task alive
+    drop(upgraded);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!weak_drop_handle.is_alive());
+    assert!(weak_drop_handle.is_finished());
+    assert!(weak_drop_handle.upgrade().is_none());
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test]
+async fn test_detach() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    let drop_handle_clone = drop_handle.clone();
+    assert!(drop_handle.is_armed());
+
+    // Detaching disarms every clone
+    let abort_handle = drop_handle.detach();
+    assert!(!drop_handle_clone.is_armed());
+    drop(drop_handle_clone);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!abort_handle.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+    // The task can still be aborted through the returned `AbortHandle`
+    abort_handle.abort();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test]
+async fn test_spawn_helpers() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    let drop_handle = crate::spawn(hold_until_aborted(&arc_counter));
+    let handle = tokio::runtime::Handle::current();
+    let arc_counter_clone = arc_counter.clone();
+    let drop_handle_on = std::thread::spawn(move || {
+        crate::spawn_on(&handle, hold_until_aborted(&arc_counter_clone))
+    })
+    .join()
+    .unwrap();
+    let drop_handle_blocking = crate::spawn_blocking(|| 42);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 3);
+    assert!(drop_handle_blocking.is_finished());
+
+    drop((drop_handle, drop_handle_on));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    tokio::task::LocalSet::new()
+        .run_until(async {
+            let drop_handle_local = crate::spawn_local(hold_until_aborted(&arc_counter));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+            drop(drop_handle_local);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(Arc::strong_count(&arc_counter), 1);
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_abort_on_drop_ext() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_join_handle = spawn_pending(&arc_counter).abort_on_drop();
+    let drop_handle = spawn_pending(&arc_counter).abort_handle().abort_on_drop();
+    let mut join_set = tokio::task::JoinSet::new();
+    join_set.spawn(async { 42 });
+    let mut group = join_set.abort_on_drop();
+    group.insert(tokio::spawn(async { 43 }));
+    let mut outputs = vec![
+        group.join_next().await.unwrap().unwrap(),
+        group.join_next().await.unwrap().unwrap(),
+    ];
+    outputs.sort_unstable();
+    assert_eq!(outputs, [42, 43]);
+    assert!(group.join_next().await.is_none());
+    assert_eq!(Arc::strong_count(&arc_counter), 3);
+
+    drop((drop_join_handle, drop_handle));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test]
+async fn test_callbacks() {
+    let aborted = Arc::new(AtomicUsize::new(0));
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let on_abort = || {
+        let aborted = aborted.clone();
+        move || {
+            aborted.fetch_add(1, Ordering::SeqCst);
+        }
+    };
+    let on_finish = || {
+        let outcomes = outcomes.clone();
+        move |outcome| outcomes.lock().unwrap().push(outcome)
+    };
+
+    // The last drop aborts the task, and runs both callbacks
+    let drop_handle = crate::spawn(std::future::pending::<()>());
+    drop_handle.on_abort(on_abort());
+    drop_handle.on_finish(on_finish());
+    drop(drop_handle.clone());
+    assert_eq!(aborted.load(Ordering::SeqCst), 0);
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+    assert_eq!(*outcomes.lock().unwrap(), [TaskOutcome::Cancelled]);
+
+    // A detached task is not aborted
+    let drop_handle = crate::spawn(async {});
+    drop_handle.on_abort(on_abort());
+    drop_handle.on_finish(on_finish());
+    let _abort_handle = drop_handle.detach();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+
+    // A task aborted explicitly is not aborted again by the last drop
+    let drop_handle = crate::spawn(std::future::pending::<()>());
+    drop_handle.on_abort(on_abort());
+    drop_handle.abort();
+    drop(drop_handle);
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+
+    // Neither is a task that already completed
+    let drop_handle = crate::spawn(async {});
+    drop_handle.on_abort(on_abort());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(drop_handle);
+    assert_eq!(aborted.load(Ordering::SeqCst), 1);
+
+    // A callback registered after the task terminated is run right away
+    let drop_handle = crate::spawn(async { panic!("task panicked") });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop_handle.on_finish(on_finish());
+    assert_eq!(
+        *outcomes.lock().unwrap(),
+        [
+            TaskOutcome::Cancelled,
+            TaskOutcome::Completed,
+            TaskOutcome::Panicked
+        ]
+    );
+}
+
+#[cfg(feature = "registry")]
+#[tokio::test]
+async fn test_registry() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    let line = line!() - 1;
+    let id = drop_handle.id();
+    let find = || {
+        crate::registry::snapshot()
+            .into_iter()
+            .find(|info| info.id == id)
+    };
+
+    let drop_handle_clone = drop_handle.clone();
+    let info = find().unwrap();
+    assert_eq!(info.location.file(), file!());
+    assert_eq!(info.location.line(), line);
+    assert_eq!(info.holders, 2);
+    assert!(info.armed);
+
+    drop((drop_handle, drop_handle_clone));
+    assert!(find().is_none());
+
+    // A Tokio task is registered whichever way its `DropHandle` is created
+    let drop_handle = DropHandle::from_abortable(spawn_pending(&arc_counter).abort_handle());
+    let id = drop_handle.id();
+    assert!(
+        crate::registry::snapshot()
+            .iter()
+            .any(|info| info.id == id && info.holders == 1)
+    );
+}
+
+#[tokio::test]
+async fn test_scope() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // The tasks still running are aborted when the scope completes
+    let arc_counter_clone = arc_counter.clone();
+    let output = crate::scope(|scope| async move {
+        scope.spawn(hold_until_aborted(&arc_counter_clone));
+        scope.spawn(async { 42 }).await.unwrap()
+    })
+    .await;
+    assert_eq!(output, 42);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // ... or when the scope future is dropped
+    let arc_counter_clone = arc_counter.clone();
+    let scope = crate::scope(|scope| async move {
+        scope.spawn(hold_until_aborted(&arc_counter_clone));
+        std::future::pending::<()>().await;
+    });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), scope)
+            .await
+            .is_err()
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // The scope can wait for its tasks instead, including those spawned by its tasks
+    let finished = Arc::new(AtomicUsize::new(0));
+    let finished_clone = finished.clone();
+    crate::scope_with(ScopeExit::Wait, |scope| async move {
+        scope.clone().spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            scope.spawn(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                finished_clone.fetch_add(1, Ordering::SeqCst);
+            });
+        });
+    })
+    .await;
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_children() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // Dropping the parent recursively aborts the children, even if they are still held
+    let parent = crate::spawn(hold_until_aborted(&arc_counter));
+    let child = parent.spawn_child(hold_until_aborted(&arc_counter));
+    let grandchild: DropHandle = spawn_pending(&arc_counter).into();
+    child.attach_child(&grandchild);
+    drop(child);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(parent.children_count(), 1);
+    assert_eq!(Arc::strong_count(&arc_counter), 4);
+    drop(parent);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(grandchild.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // An explicit abort cascades too, but not to the detached children
+    let parent = crate::spawn(hold_until_aborted(&arc_counter));
+    let child = parent.spawn_child(hold_until_aborted(&arc_counter));
+    let detached_child = parent.spawn_child(hold_until_aborted(&arc_counter));
+    assert!(detached_child.detach_from_parent());
+    assert!(!detached_child.detach_from_parent());
+    parent.abort();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(child.is_finished());
+    assert!(!detached_child.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+    // The finished children are released by a long-lived parent
+    let parent = crate::spawn(hold_until_aborted(&arc_counter));
+    for _ in 0..100 {
+        drop(parent.spawn_child(async {}));
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let child = parent.spawn_child(hold_until_aborted(&arc_counter));
+    assert_eq!(parent.children_count(), 1);
+    assert!(child.detach_from_parent());
+    assert_eq!(parent.children_count(), 0);
+
+    // A child attached to a task that was already aborted is aborted right away
+    parent.abort();
+    let child = parent.spawn_child(hold_until_aborted(&arc_counter));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(child.is_finished());
+    assert_eq!(child.abort_reason(), Some(AbortReason::ParentAborted));
+    assert_eq!(parent.children_count(), 0);
+
+    // The children of a detached task keep running along with it
+    let parent = crate::spawn(hold_until_aborted(&arc_counter));
+    let child_abort_handle =
+        AbortHandle::clone(&parent.spawn_child(hold_until_aborted(&arc_counter)));
+    let parent_abort_handle = parent.detach();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!parent_abort_handle.is_finished());
+    assert!(!child_abort_handle.is_finished());
+    parent_abort_handle.abort();
+    child_abort_handle.abort();
+}
+
+#[test]
+fn test_children_concurrent_cycle() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let _guard = runtime.enter();
+    for _ in 0..20 {
+        let first = crate::spawn(std::future::pending::<()>());
+        let second = crate::spawn(std::future::pending::<()>());
+
+        // Attaching each task to the other at the same time cannot create a cycle: one of them panics
+        let attach = |parent: &DropHandle, child: &DropHandle| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                parent.attach_child(child);
+            }))
+            .is_ok()
+        };
+        let attached = std::thread::scope(|scope| {
+            let thread = scope.spawn(|| attach(&first, &second));
+            let attached = attach(&second, &first);
+            usize::from(attached) + usize::from(thread.join().unwrap())
+        });
+        assert_eq!(attached, 1);
+        first.abort();
+        second.abort();
+    }
+}
+
+#[tokio::test]
+#[should_panic(expected = "cannot attach task")]
+async fn test_children_cycle() {
+    let parent = crate::spawn(std::future::pending::<()>());
+    let child = parent.spawn_child(std::future::pending::<()>());
+    child.attach_child(&parent);
+}
+
+#[tokio::test]
+async fn test_unique_drop_handle() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // Dropping a `UniqueDropHandle` aborts the task
+    let join_handle = spawn_pending(&arc_counter);
+    let abort_handle = join_handle.abort_handle();
+    let unique_drop_handle: UniqueDropHandle = join_handle.into();
+    drop(unique_drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(abort_handle.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // Once shared, the task is only aborted when the last `DropHandle` is dropped
+    let unique_drop_handle: UniqueDropHandle = spawn_pending(&arc_counter).into();
+    let drop_handle = unique_drop_handle.into_shared();
+    let drop_handle_clone = drop_handle.clone();
+    drop(drop_handle);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!drop_handle_clone.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+    drop(drop_handle_clone);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[tokio::test]
+async fn test_local_drop_handle() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    tokio::task::LocalSet::new()
+        .run_until(async {
+            let local_drop_handle = LocalDropHandle::spawn_local(hold_until_aborted(&arc_counter));
+            let local_drop_handle_clone = local_drop_handle.clone();
+            let local_drop_handle_from: LocalDropHandle =
+                tokio::task::spawn_local(hold_until_aborted(&arc_counter)).into();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(Arc::strong_count(&arc_counter), 3);
+
+            // The task is only aborted when the last clone is dropped
+            drop(local_drop_handle);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(!local_drop_handle_clone.is_finished());
+            drop(local_drop_handle_clone);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+            drop(local_drop_handle_from);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+            // A local task with a `!Send` output can still be converted into a `DropHandle`
+            let drop_handle: DropHandle =
+                tokio::task::spawn_local(async { std::rc::Rc::new(42) }).into();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(drop_handle.is_finished());
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_abort_reason() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // An explicit abort is recorded, and cascades to the children as a parent abort
+    let parent: DropHandle = spawn_pending(&arc_counter).into();
+    let child = parent.spawn_child(std::future::pending::<()>());
+    assert_eq!(parent.abort_reason(), None);
+    parent.abort();
+    assert_eq!(parent.abort_reason(), Some(AbortReason::Explicit));
+    assert_eq!(child.abort_reason(), Some(AbortReason::ParentAborted));
+
+    // Only the first reason is recorded
+    parent.abort_with(AbortReason::custom("too late"));
+    assert_eq!(parent.abort_reason(), Some(AbortReason::Explicit));
+
+    // The last handle records its drop, before the children get aborted
+    let parent: DropHandle = spawn_pending(&arc_counter).into();
+    let child = parent.spawn_child(std::future::pending::<()>());
+    let custom = parent.spawn_child(std::future::pending::<()>());
+    custom.abort_with(AbortReason::custom("custom"));
+    drop(parent);
+    assert_eq!(child.abort_reason(), Some(AbortReason::ParentAborted));
+    assert_eq!(custom.abort_reason(), Some(AbortReason::custom("custom")));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // No reason is recorded for a task that already completed
+    let drop_handle = crate::spawn(async {});
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop_handle.abort();
+    assert_eq!(drop_handle.abort_reason(), None);
+
+    // Aborting a `DropJoinHandle` records the reason, and cascades to the children
+    let drop_join_handle: DropJoinHandle<()> = tokio::spawn(std::future::pending()).into();
+    let child = drop_join_handle
+        .drop_handle()
+        .spawn_child(std::future::pending::<()>());
+    drop_join_handle.abort();
+    assert_eq!(
+        drop_join_handle.drop_handle().abort_reason(),
+        Some(AbortReason::Explicit)
+    );
+    assert_eq!(child.abort_reason(), Some(AbortReason::ParentAborted));
+}
+
+#[tokio::test]
+async fn test_abort_and_wait() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // The task has released its resources once the wait resolves
+    let drop_handle = crate::spawn(hold_until_aborted(&arc_counter));
+    let drop_handle_clone = drop_handle.clone();
+    assert_eq!(
+        drop_handle.abort_and_wait().await,
+        Some(TaskOutcome::Cancelled)
+    );
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+    assert!(drop_handle_clone.is_finished());
+
+    // The outcome of a task that already terminated is reported as is
+    let drop_handle = crate::spawn(async {});
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(drop_handle.shutdown().await, Some(TaskOutcome::Completed));
+
+    // A graceful task is given a chance to complete, and is not shut down again by the drop of its handle
+    let drop_handle = spawn_graceful(Duration::from_secs(5), |token| async move {
+        token.cancelled().await;
+    });
+    let aborted = Arc::new(AtomicUsize::new(0));
+    let aborted_clone = aborted.clone();
+    drop_handle.on_abort(move || {
+        aborted_clone.fetch_add(1, Ordering::SeqCst);
+    });
+    assert_eq!(drop_handle.shutdown().await, Some(TaskOutcome::Completed));
+    assert_eq!(aborted.load(Ordering::SeqCst), 0);
+
+    // A task created with `DropHandle::watch` is watched, so its outcome is known
+    let drop_handle = DropHandle::watch(spawn_pending(&arc_counter));
+    assert_eq!(drop_handle.shutdown().await, Some(TaskOutcome::Cancelled));
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+    let drop_handle = DropHandle::watch(tokio::spawn(async { panic!("boom") }));
+    assert_eq!(drop_handle.wait().await, Some(TaskOutcome::Panicked));
+
+    // Without a watcher, the outcome is unknown
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    assert_eq!(drop_handle.shutdown().await, None);
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).abort_handle().into();
+    assert_eq!(drop_handle.shutdown().await, None);
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
+
+#[test]
+fn test_wait_without_time_driver() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let drop_handle = DropHandle::watch(tokio::spawn(std::future::pending::<()>()));
+        assert_eq!(
+            drop_handle.abort_and_wait().await,
+            Some(TaskOutcome::Cancelled)
+        );
+    });
+}
+
+#[tokio::test]
+async fn test_deadline() {
+    let arc_counter = Arc::new(String::from("counter"));
+
+    // The deadline aborts the task even though it is still held
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    let drop_handle =
+        drop_handle.with_deadline(tokio::time::Instant::now() + Duration::from_millis(100));
+    assert!(drop_handle.deadline().is_some());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(drop_handle.is_finished());
+    assert_eq!(drop_handle.abort_reason(), Some(AbortReason::Deadline));
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // Any clone can extend or clear the deadline
+    let completed = crate::spawn_with_timeout(Duration::from_millis(100), async {});
+    let drop_handle =
+        crate::spawn_with_timeout(Duration::from_millis(100), std::future::pending::<()>());
+    let drop_handle_clone = drop_handle.clone();
+    drop_handle_clone.set_deadline(tokio::time::Instant::now() + Duration::from_millis(200));
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert!(!drop_handle.is_finished());
+    drop_handle_clone.clear_deadline();
+    assert_eq!(drop_handle.deadline(), None);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert!(!drop_handle.is_finished());
+
+    // A task that completed before its deadline is not reported as aborted
+    assert_eq!(completed.abort_reason(), None);
+}
+
+#[tokio::test]
+async fn test_lease() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let drop_handle: DropHandle = spawn_pending(&arc_counter).into();
+    let drop_handle = drop_handle.with_lease(Duration::from_millis(150));
+    let drop_handle_clone = drop_handle.clone();
+
+    // Any clone keeps the task alive by renewing the lease
+    for _ in 0..3 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(drop_handle_clone.renew());
+    }
+    assert!(!drop_handle.is_finished());
+
+    // The task is aborted once the lease expires, even though it is still held
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(drop_handle.is_finished());
+    assert_eq!(drop_handle.abort_reason(), Some(AbortReason::LeaseExpired));
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+    // An expired lease can no longer be renewed
+    assert!(!drop_handle_clone.renew());
+
+    // A plain deadline cannot be renewed
+    let drop_handle = crate::spawn_with_timeout(Duration::from_secs(5), async {});
+    assert!(!drop_handle.renew());
+}
+
+#[tokio::test]
+async fn test_supervise() {
+    let arc_counter = Arc::new(String::from("counter"));
+    let incarnations = Arc::new(AtomicUsize::new(0));
+
+    // The task is restarted after a panic or a return, with a backoff, until the budget is exhausted
+    let policy = RestartPolicy::default()
+        .initial_backoff(Duration::from_millis(10))
+        .max_backoff(Duration::from_millis(20))
+        .max_restarts(2);
+    let incarnations_clone = incarnations.clone();
+    let supervisor = crate::supervise_with(policy, move || {
+        let incarnation = incarnations_clone.fetch_add(1, Ordering::SeqCst);
+        async move { assert!(incarnation != 0, "first incarnation") }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(incarnations.load(Ordering::SeqCst), 3);
+    assert_eq!(supervisor.restarts(), 2);
+    assert_eq!(supervisor.last_error(), Some(SupervisorError::Exited));
+    assert!(supervisor.is_finished());
+
+    // The backoff is reset once an incarnation ran for longer than it
+    let policy = RestartPolicy::default()
+        .initial_backoff(Duration::from_millis(10))
+        .max_backoff(Duration::from_secs(1))
+        .max_restarts(4);
+    let starts = Arc::new(Mutex::new(Vec::new()));
+    let starts_clone = starts.clone();
+    let _supervisor = crate::supervise_with(policy, move || {
+        let healthy = {
+            let mut starts = starts_clone.lock().unwrap();
+            starts.push(tokio::time::Instant::now());
+            starts.len() == 4
+        };
+        async move {
+            if healthy {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let starts = starts.lock().unwrap().clone();
+    assert_eq!(starts.len(), 5);
+    // Without the reset, the last delay would have been 80 milliseconds
+    assert!(starts[4] - starts[3] < Duration::from_millis(200 + 50));
+
+    // Dropping the supervisor aborts the current incarnation
+    let counter = arc_counter.clone();
+    let supervisor = crate::supervise(move || {
+        let counter = counter.clone();
+        async move {
+            std::future::pending::<()>().await;
+            drop(counter);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(supervisor.restarts(), 0);
+    assert_eq!(supervisor.last_error(), None);
+    drop(supervisor);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+
+    // An explicit abort is recorded on the restart loop
+    let supervisor = crate::supervise(std::future::pending::<()>);
+    supervisor.abort();
+    assert_eq!(
+        supervisor.drop_handle().abort_reason(),
+        Some(AbortReason::Explicit)
+    );
+}
+
+#[tokio::test]
+async fn test_panic_policy() {
+    // The payload is kept until a policy is set, so a sink never misses a panic
+    let drop_handle = crate::spawn(async { panic!("boom") });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let sunk = Arc::new(Mutex::new(Vec::new()));
+    let sunk_clone = sunk.clone();
+    drop_handle.set_panic_policy(PanicPolicy::sink(move |id, payload| {
+        let message = *payload.downcast::<&str>().unwrap();
+        sunk_clone.lock().unwrap().push((id, message));
+    }));
+    assert_eq!(*sunk.lock().unwrap(), vec![(drop_handle.id(), "boom")]);
+    assert_eq!(drop_handle.wait().await, Some(TaskOutcome::Panicked));
+
+    // The panic is re-raised in the caller awaiting the task
+    let drop_handle = crate::spawn_blocking(|| panic!("boom"));
+    drop_handle.set_panic_policy(PanicPolicy::Resume);
+    let payload = tokio::spawn(async move { drop_handle.wait().await })
+        .await
+        .unwrap_err()
+        .into_panic();
+    assert_eq!(*payload.downcast::<&str>().unwrap(), "boom");
+
+    // Other tasks are not affected
+    let drop_handle = crate::spawn(async { 42 });
+    drop_handle.set_panic_policy(PanicPolicy::Resume);
+    assert_eq!(drop_handle.wait().await, Some(TaskOutcome::Completed));
+}
+
+#[cfg(feature = "tracing")]
+#[tokio::test]
+async fn test_spawn_instrumented() {
+    let _log_level = LOG_LEVEL.read().await;
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let drop_handle = crate::spawn_instrumented("worker", async {
+        tracing::info!("working");
+        std::future::pending::<()>().await;
+    });
+    let id = drop_handle.id();
+    let completed = crate::spawn_instrumented("oneshot", async {});
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(drop_handle);
+    drop(completed);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Every event is emitted in the span of the task, with its id and name
+    let logs = logs.contents();
+    let id = format!("task.id={id}");
+    for event in [
+        "created location=",
+        "working",
+        "aborted reason=last handle dropped",
+        "finished outcome=Cancelled",
+    ] {
+        assert!(
+            logs.lines().any(|line| line.contains("task{")
+                && line.contains("task.name=worker")
+                && line.contains(&id)
+                && line.contains(event)),
+            "missing event {event:?} in:\n{logs}"
+        );
+    }
+
+    // The events of the `DropHandle` have the same `task.id` field as the span
+    assert!(
+        logs.lines()
+            .any(|line| line.contains("drop DropHandle: abort task") && line.contains(&id)),
+        "missing drop event in:\n{logs}"
+    );
+
+    // A task that already completed is not reported as aborted by the drop of its last handle
+    assert!(
+        logs.lines().any(|line| line.contains("task.name=oneshot")
+            && line.contains("finished outcome=Completed")),
+        "missing finished event in:\n{logs}"
+    );
+    assert!(
+        !logs
+            .lines()
+            .any(|line| line.contains("task.name=oneshot") && line.contains("aborted")),
+        "unexpected aborted event in:\n{logs}"
+    );
+}
+
+// The task id compared and hashed by `DropHandle` never changes, despite its interior mutability
+#[allow(clippy::mutable_key_type)]
+#[tokio::test]
+async fn test_task_identity() {
+    let first = crate::spawn(std::future::pending::<()>());
+    let second = crate::spawn(std::future::pending::<()>());
+    assert_eq!(first, first.clone());
+    assert_ne!(first, second);
+    assert_eq!(first.cmp(&second), first.id().cmp(&second.id()));
+
+    // Sets of handles can be queried by task id
+    let drop_handles: std::collections::BTreeSet<DropHandle> =
+        [first.clone(), first.clone(), second.clone()].into();
+    assert_eq!(drop_handles.len(), 2);
+    assert!(drop_handles.contains(&second.id()));
+    let mut drop_handles: std::collections::HashSet<DropHandle> =
+        drop_handles.into_iter().collect();
+    assert!(drop_handles.remove(&first.id()));
+    assert!(!drop_handles.contains(&first.id()));
+}
+
+#[tokio::test]
+async fn test_named_drop_handle() {
+    let _log_level = LOG_LEVEL.read().await;
+    #[cfg(feature = "tracing")]
+    let logs = Logs::default();
+    #[cfg(feature = "tracing")]
+    let _guard = {
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    };
+
+    let drop_handle = crate::spawn_named("worker", std::future::pending::<()>());
+    let id = drop_handle.id();
+    assert_eq!(drop_handle.name(), Some("worker"));
+    assert_eq!(drop_handle.to_string(), format!("worker (task {id})"));
+    assert!(format!("{drop_handle:?}").contains(r#"name: Some("worker")"#));
+
+    // Only the first name is kept
+    let drop_handle = drop_handle.with_name("renamed");
+    assert_eq!(drop_handle.name(), Some("worker"));
+
+    let unnamed = crate::spawn(std::future::pending::<()>());
+    assert_eq!(unnamed.name(), None);
+    assert_eq!(unnamed.to_string(), format!("task {}", unnamed.id()));
+    let unnamed = unnamed.with_name(String::from("late"));
+    assert_eq!(unnamed.to_string(), format!("late (task {})", unnamed.id()));
+
+    #[cfg(feature = "registry")]
+    assert!(
+        crate::registry::snapshot()
+            .iter()
+            .any(|info| info.id == id && info.name.as_deref() == Some("worker"))
+    );
+
+    let child = crate::spawn_named("child", std::future::pending::<()>());
+    drop_handle.attach_child(&child);
+    child.detach_from_parent();
+
+    // A graceful task ignoring its token is aborted once the grace period has elapsed
+    let graceful = crate::spawn_graceful(Duration::from_millis(10), |_token| {
+        std::future::pending::<()>()
+    })
+    .with_name("graceful");
+    drop(graceful);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    drop(drop_handle);
+    #[cfg(feature = "tracing")]
+    {
+        let logs = logs.contents();
+        for (event, name) in [
+            ("drop DropHandle: abort task", r#"name="worker""#),
+            ("attach child task", r#"name="child" parent.id="#),
+            ("attach child task", r#"parent.name="worker""#),
+            ("detach child task", r#"name="child" parent.id="#),
+            ("detach child task", r#"parent.name="worker""#),
+            ("grace period elapsed: abort task", r#"name="graceful""#),
+        ] {
+            assert!(
+                logs.lines()
+                    .any(|line| line.contains(event) && line.contains(name)),
+                "missing {name} in event {event:?} in:\n{logs}"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[tokio::test]
+async fn test_log_level() {
+    let _log_level = LOG_LEVEL.write().await;
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // No event is emitted once the log level is off
+    crate::set_log_level(crate::LogLevel::Off);
+    drop(crate::spawn(std::future::pending::<()>()));
+    crate::set_log_level(crate::LogLevel::Trace);
+    assert_eq!(logs.contents(), "");
+
+    let drop_handle = crate::spawn(std::future::pending::<()>());
+    let id = format!("task.id={}", drop_handle.id());
+    drop(drop_handle);
+    let logs = logs.contents();
+    assert!(
+        logs.lines().any(|line| line.contains("TRACE")
+            && line.contains("drop DropHandle")
+            && line.contains(&id)),
+        "missing drop event in:\n{logs}"
+    );
+    // An unnamed task has no `name` field
+    assert!(!logs.contains("name="), "unexpected name field in:\n{logs}");
+}
+
+/// A `log` logger capturing the records of this crate.
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+struct LogRecords(Mutex<Vec<String>>);
+
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+impl log::Log for LogRecords {
+    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if record.target().starts_with("drop_handle") {
+            let record = format!("{} {}", record.level(), record.args());
+            self.0.lock().expect("poisoned records").push(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+static LOG_RECORDS: LogRecords = LogRecords(Mutex::new(Vec::new()));
+
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+#[tokio::test]
+async fn test_log_backend() {
+    let _log_level = LOG_LEVEL.write().await;
+    log::set_logger(&LOG_RECORDS).expect("a logger is already set");
+    log::set_max_level(log::LevelFilter::Trace);
+    // The records of a task, as other tests log concurrently
+    let records_of = |drop_handle: &DropHandle| {
+        let id = format!("task.id={}", drop_handle.id());
+        LOG_RECORDS
+            .0
+            .lock()
+            .expect("poisoned records")
+            .iter()
+            .filter(|record| record.split(' ').any(|word| word == id))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    // The fields are appended to the message, skipping the name of an unnamed task
+    let named = crate::spawn_named("worker", std::future::pending::<()>());
+    let unnamed = crate::spawn(std::future::pending::<()>());
+    drop(named.clone());
+    drop(unnamed.clone());
+    let records = records_of(&named);
+    assert!(
+        records
+            .iter()
+            .any(|record| record.starts_with("TRACE drop DropHandle ")
+                && record.contains(r#" name="worker" holders=1"#)),
+        "missing drop record in {records:?}"
+    );
+    let records = records_of(&unnamed);
+    assert!(
+        records
+            .iter()
+            .any(|record| record.starts_with("TRACE drop DropHandle ")
+                && record.ends_with(" holders=1")),
+        "missing drop record in {records:?}"
+    );
+    assert!(
+        !records.iter().any(|record| record.contains("name=")),
+        "unexpected name field in {records:?}"
+    );
+
+    // No record is emitted once the log level is off
+    crate::set_log_level(crate::LogLevel::Off);
+    let drop_handle = crate::spawn(std::future::pending::<()>());
+    drop(drop_handle.clone());
+    crate::set_log_level(crate::LogLevel::Trace);
+    assert_eq!(records_of(&drop_handle), Vec::<String>::new());
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn test_futures_backend() {
+    use futures_util::future::{AbortHandle, Abortable};
+
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    let join_handle = tokio::spawn(Abortable::new(
+        std::future::pending::<()>(),
+        abort_registration,
+    ));
+    let drop_handle: DropHandle<AbortHandle> = abort_handle.into();
+    drop(drop_handle.clone());
+    assert!(!drop_handle.is_aborted());
+
+    drop(drop_handle);
+    assert!(join_handle.await.unwrap().is_err());
+}
+
+#[cfg(feature = "async-task")]
+#[test]
+fn test_async_task_backend() {
+    use crate::AsyncTaskHandle;
+
+    let arc_counter = Arc::new(String::from("counter"));
+    let queue = Arc::new(Mutex::new(Vec::new()));
+    let queue_clone = queue.clone();
+    let arc_counter_clone = arc_counter.clone();
+    let (runnable, task) = async_task::spawn(
+        async move {
+            std::future::pending::<()>().await;
+            drop(arc_counter_clone);
+        },
+        move |runnable| queue_clone.lock().unwrap().push(runnable),
+    );
+    runnable.run();
+
+    let drop_handle: DropHandle<AsyncTaskHandle<()>> = task.into();
+    drop(drop_handle.clone());
+    assert!(!drop_handle.is_finished());
+    assert_eq!(Arc::strong_count(&arc_counter), 2);
+
+    // Dropping the last handle cancels the task, whose future is dropped by the executor
+    drop(drop_handle);
+    for runnable in queue.lock().unwrap().drain(..) {
+        runnable.run();
+    }
+    assert_eq!(Arc::strong_count(&arc_counter), 1);
+}
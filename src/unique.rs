@@ -0,0 +1,115 @@
+use crate::{DropHandle, shared::Shared};
+use std::{fmt, ops::Deref, panic::Location};
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// A single-owner handle that aborts the task when dropped.
+///
+/// Unlike `DropHandle`, a `UniqueDropHandle` cannot be cloned: it stores the `AbortHandle` inline,
+/// without allocating any shared state, and aborts the task unconditionally when dropped.
+/// It can be turned into a `DropHandle` with `into_shared()` when the task has to be held by several owners.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::UniqueDropHandle;
+/// use std::future::pending;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let unique_drop_handle: UniqueDropHandle = tokio::spawn(pending::<()>()).into();
+///     let drop_handle = unique_drop_handle.into_shared();
+///     let drop_handle_clone = drop_handle.clone();
+///     // The task will be aborted when both `drop_handle` and `drop_handle_clone` go out of scope.
+/// }
+/// ```
+pub struct UniqueDropHandle {
+    /// Only `None` once the handle has been consumed by `into_shared()` or `detach()`.
+    abort_handle: Option<AbortHandle>,
+}
+
+impl UniqueDropHandle {
+    #[track_caller]
+    fn new(abort_handle: AbortHandle) -> Self {
+        debug!(
+            task.id = %abort_handle.id(),
+            location = %Location::caller(),
+            "create UniqueDropHandle"
+        );
+        Self {
+            abort_handle: Some(abort_handle),
+        }
+    }
+
+    const fn inner(&self) -> &AbortHandle {
+        self.abort_handle
+            .as_ref()
+            .expect("UniqueDropHandle is only emptied when consumed")
+    }
+
+    fn take(mut self) -> AbortHandle {
+        self.abort_handle
+            .take()
+            .expect("UniqueDropHandle is only emptied when consumed")
+    }
+
+    /// Turns this handle into a `DropHandle`, that can be cloned to share the task between several owners.
+    #[must_use]
+    #[track_caller]
+    pub fn into_shared(self) -> DropHandle {
+        DropHandle::new(Shared::new(self.take()))
+    }
+
+    /// Detaches the task, so it keeps running after this handle is dropped, and returns the underlying `AbortHandle`.
+    #[must_use = "the task can no longer be aborted if the returned `AbortHandle` is dropped"]
+    pub fn detach(self) -> AbortHandle {
+        let abort_handle = self.take();
+        debug!(task.id = %abort_handle.id(), "detach UniqueDropHandle");
+        abort_handle
+    }
+}
+
+impl fmt::Debug for UniqueDropHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UniqueDropHandle")
+            .field("abort_handle", &self.abort_handle)
+            .finish()
+    }
+}
+
+impl Deref for UniqueDropHandle {
+    type Target = AbortHandle;
+
+    fn deref(&self) -> &AbortHandle {
+        self.inner()
+    }
+}
+
+impl From<AbortHandle> for UniqueDropHandle {
+    #[track_caller]
+    fn from(value: AbortHandle) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<JoinHandle<T>> for UniqueDropHandle {
+    #[track_caller]
+    fn from(value: JoinHandle<T>) -> Self {
+        Self::new(value.abort_handle())
+    }
+}
+
+impl From<UniqueDropHandle> for DropHandle {
+    #[track_caller]
+    fn from(value: UniqueDropHandle) -> Self {
+        value.into_shared()
+    }
+}
+
+/// When the `UniqueDropHandle` is dropped, the task will be aborted, unless it has been turned into a `DropHandle` or detached.
+impl Drop for UniqueDropHandle {
+    fn drop(&mut self) {
+        if let Some(abort_handle) = &self.abort_handle {
+            debug!(task.id = %abort_handle.id(), "drop UniqueDropHandle: abort task");
+            abort_handle.abort();
+        }
+    }
+}
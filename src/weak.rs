@@ -0,0 +1,67 @@
+use crate::{DropHandle, shared::Shared};
+use std::sync::{Arc, Weak};
+use tokio::task::{AbortHandle, Id};
+
+/// A weak reference to a `DropHandle`, that observes the task without keeping it alive.
+///
+/// A `WeakDropHandle` is created with `DropHandle::downgrade()`, and never prevents the last `DropHandle` from aborting the task.
+///
+/// Example usage:
+/// ```
+/// use drop_handle::DropHandle;
+/// use std::future::pending;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let drop_handle: DropHandle = tokio::spawn(pending::<()>()).into();
+///     let weak_drop_handle = drop_handle.downgrade();
+///     assert!(weak_drop_handle.is_alive());
+///
+///     drop(drop_handle);
+///     assert!(!weak_drop_handle.is_alive());
+///     assert!(weak_drop_handle.upgrade().is_none());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WeakDropHandle {
+    shared: Weak<Shared<AbortHandle>>,
+    abort_handle: AbortHandle,
+}
+
+impl WeakDropHandle {
+    pub(crate) fn new(drop_handle: &DropHandle) -> Self {
+        Self {
+            shared: Arc::downgrade(&drop_handle.0),
+            abort_handle: drop_handle.0.abort_handle.clone(),
+        }
+    }
+
+    /// Attempts to get a `DropHandle` back, keeping the task alive again.
+    ///
+    /// Returns `None` if the last `DropHandle` has already been dropped.
+    #[must_use]
+    pub fn upgrade(&self) -> Option<DropHandle> {
+        let shared = self.shared.upgrade()?;
+        shared.holders.try_acquire().then(|| DropHandle(shared))
+    }
+
+    /// Returns `true` if at least one `DropHandle` still holds the task.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        self.shared
+            .upgrade()
+            .is_some_and(|shared| shared.holders.count() > 0)
+    }
+
+    /// Returns `true` if the task has finished, whether it completed, panicked or was aborted.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.abort_handle.is_finished()
+    }
+
+    /// Returns the task id.
+    #[must_use]
+    pub fn id(&self) -> Id {
+        self.abort_handle.id()
+    }
+}